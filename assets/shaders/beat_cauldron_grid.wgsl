@@ -0,0 +1,223 @@
+// Beat Cauldron grid generation: one invocation per grid cell, writing straight into the
+// planar storage. Mirrors `spawn_gaussian_grid` in src/beat_cauldron/mod.rs.
+
+// Degree 0 SH basis constant (gaussian::color::SH_C0)
+const SH_C0: f32 = 0.2820948;
+
+struct SphericalHarmonic {
+    coefficients: array<f32, 48>,
+}
+
+
+// Grid params (must match BeatCauldronGridParams)
+struct BeatCauldronGridParams {
+    grid_width:             u32,
+    grid_height:            u32,
+    noise_octaves:          u32,
+    color_noise_octaves:    u32,
+    cell_spacing:           vec2<f32>,
+    half_extents:           vec2<f32>,
+    noise_offset:           vec2<f32>,
+    color_noise_frequency:  vec2<f32>,
+    color_noise_offset:     vec2<f32>,
+    noise_base_frequency:   f32,
+    noise_lacunarity:       f32,
+    noise_persistence:      f32,
+    color_noise_lacunarity: f32,
+    color_noise_gain:       f32,
+    color_noise_seed:       u32,
+    grid_plane_z:           f32,
+    altitude_variation:     f32,
+    scale_multiplier:       f32,
+    opacity_base:           f32,
+    min_scale:              vec3<f32>,
+    opacity_variation:      f32,
+    max_scale:              vec3<f32>,
+    _pad:                   f32,
+}
+@group(0) @binding(0) var<uniform> params: BeatCauldronGridParams;
+
+
+// Output buffers (read-write), same layout as PlanarStorageGaussian3d
+@group(1) @binding(0) var<storage, read_write>      out_position_visibility:     array<vec4<f32>>;
+@group(1) @binding(1) var<storage, read_write>      out_spherical_harmonics:     array<SphericalHarmonic>;
+@group(1) @binding(2) var<storage, read_write>      out_rotation:                array<vec4<f32>>;
+@group(1) @binding(3) var<storage, read_write>      out_scale_opacity:           array<vec4<f32>>;
+
+
+
+
+
+
+
+// --- Value noise (same lattice hash as gaussian::noise::smooth_value_noise) ---
+// gaussian::rng::hash_u32 (lowbias32)
+fn hash_u32(x: u32) -> u32 {
+    var h = x;
+    h ^= h >> 16u;
+    h *= 0x7feb352du;
+    h ^= h >> 15u;
+    h *= 0x846ca68bu;
+    h ^= h >> 16u;
+    return h;
+}
+
+// gaussian::rng::hash_to_unit(cell.x, cell.y)
+fn lattice_value(p: vec2<f32>) -> f32 {
+    let cell = bitcast<vec2<u32>>(vec2<i32>(p));
+    let h = hash_u32(cell.y ^ hash_u32(cell.x));
+    return f32(h >> 8u) / 16777216.0;
+}
+
+fn smooth_value_noise(p: vec2<f32>) -> f32 {
+    let cell = floor(p);
+    let frac = p - cell;
+
+    let c00 = lattice_value(cell);
+    let c10 = lattice_value(cell + vec2<f32>(1.0, 0.0));
+    let c01 = lattice_value(cell + vec2<f32>(0.0, 1.0));
+    let c11 = lattice_value(cell + vec2<f32>(1.0, 1.0));
+
+    let fade = frac * frac * (3.0 - 2.0 * frac);
+
+    let nx0 = mix(c00, c10, fade.x);
+    let nx1 = mix(c01, c11, fade.x);
+    return clamp(mix(nx0, nx1, fade.y), 0.0, 1.0);
+}
+
+// BeatCauldronSettings::sample_noise
+fn sample_noise(grid_position: vec2<f32>, offset: vec2<f32>) -> f32 {
+    if (params.noise_octaves == 0u) {
+        return 0.5;
+    }
+
+    let base_position = grid_position * params.cell_spacing + params.noise_offset + offset;
+    var value = 0.0;
+    var amplitude = 1.0;
+    var frequency = params.noise_base_frequency;
+    var max_value = 0.0;
+
+    for (var i = 0u; i < params.noise_octaves; i = i + 1u) {
+        value += amplitude * smooth_value_noise(base_position * frequency);
+        max_value += amplitude;
+        amplitude *= params.noise_persistence;
+        frequency *= params.noise_lacunarity;
+    }
+
+    if (max_value > 0.0) {
+        return clamp(value / max_value, 0.0, 1.0);
+    }
+    return 0.5;
+}
+
+
+
+// --- Seeded 2D Perlin gradient noise, ~[-1, 1] ---
+// Not bit-identical to the noise crate's permutation table, but the same character.
+fn pcg_hash(v: u32) -> u32 {
+    let state = v * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn gradient(cell: vec2<i32>) -> vec2<f32> {
+    let h = pcg_hash(bitcast<u32>(cell.x) ^ pcg_hash(bitcast<u32>(cell.y) ^ pcg_hash(params.color_noise_seed)));
+    let angle = f32(h & 0xffffu) / 65536.0 * 6.28318530718;
+    return vec2<f32>(cos(angle), sin(angle));
+}
+
+fn perlin2(p: vec2<f32>) -> f32 {
+    let cell = vec2<i32>(floor(p));
+    let f = p - floor(p);
+    let u = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+    let n00 = dot(gradient(cell),                       f);
+    let n10 = dot(gradient(cell + vec2<i32>(1, 0)),     f - vec2<f32>(1.0, 0.0));
+    let n01 = dot(gradient(cell + vec2<i32>(0, 1)),     f - vec2<f32>(0.0, 1.0));
+    let n11 = dot(gradient(cell + vec2<i32>(1, 1)),     f - vec2<f32>(1.0, 1.0));
+
+    // Unit gradients give a max of sqrt(0.5); rescale to ~[-1, 1]
+    return mix(mix(n00, n10, u.x), mix(n01, n11, u.x), u.y) * 1.41421356;
+}
+
+// fbm2_perlin in spawn_gaussian_grid, remapped to [0, 1]
+fn fbm2_perlin(p: vec2<f32>) -> f32 {
+    var amp = 0.5;
+    var sum = 0.0;
+    var norm = 0.0;
+    var freq = 1.0;
+
+    for (var i = 0u; i < params.color_noise_octaves; i = i + 1u) {
+        sum += amp * perlin2(p * freq);
+        norm += amp;
+        freq *= params.color_noise_lacunarity;
+        amp *= params.color_noise_gain;
+    }
+
+    var v = 0.0;
+    if (norm > 0.0) {
+        v = sum / norm;
+    }
+    return clamp(v * 0.5 + 0.5, 0.0, 1.0);
+}
+
+
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
+
+    let x = global_id.x;
+    let y = global_id.y;
+
+    if (x >= params.grid_width || y >= params.grid_height) {
+        return;
+    }
+
+    let index = y * params.grid_width + x;
+
+    // Guard against a cloud that hasn't been resized yet
+    if (index >= arrayLength(&out_position_visibility)) {
+        return;
+    }
+
+    let grid_position = vec2<f32>(f32(x), f32(y));
+
+    let world_x = f32(x) * params.cell_spacing.x - params.half_extents.x;
+    let world_y = params.half_extents.y - f32(y) * params.cell_spacing.y;
+
+    let base_noise      = sample_noise(grid_position, vec2<f32>(0.0, 0.0));
+    let color_noise     = sample_noise(grid_position, vec2<f32>(37.0, 91.0));
+    let secondary_noise = sample_noise(grid_position, vec2<f32>(-73.0, 19.0));
+    let altitude_noise  = sample_noise(grid_position, vec2<f32>(17.0, -53.0));
+    let opacity_noise   = sample_noise(grid_position, vec2<f32>(89.0, -131.0));
+
+    let brightness = fbm2_perlin(grid_position * params.color_noise_frequency + params.color_noise_offset);
+
+    let altitude = params.grid_plane_z + (altitude_noise * 2.0 - 1.0) * params.altitude_variation;
+
+    let t     = vec3<f32>(base_noise, color_noise, secondary_noise);
+    let scale = mix(params.min_scale, params.max_scale, t) * params.scale_multiplier;
+
+    let opacity = clamp(
+        params.opacity_base + (opacity_noise * 2.0 - 1.0) * params.opacity_variation,
+        0.0,
+        1.0,
+    );
+
+    var sh: SphericalHarmonic;
+    for (var i = 0; i < 48; i = i + 1) {
+        sh.coefficients[i] = 0.0;
+    }
+    // gaussian::color::rgb_to_sh_dc
+    let sh_dc = (brightness - 0.5) / SH_C0;
+    sh.coefficients[0] = sh_dc;
+    sh.coefficients[1] = sh_dc;
+    sh.coefficients[2] = sh_dc;
+
+
+    // --- Write to Output Buffers ---
+    out_position_visibility[index]  = vec4<f32>(world_x, world_y, altitude, 1.0);
+    out_spherical_harmonics[index]  = sh;
+    out_rotation[index]             = vec4<f32>(1.0, 0.0, 0.0, 0.0);
+    out_scale_opacity[index]        = vec4<f32>(scale, opacity);
+}
@@ -1,20 +1,66 @@
+// Rounded rectangle; zero half extents make a circle of `radius`
+struct FluidObstacle {
+    center: vec2<f32>,
+    half_extents: vec2<f32>,
+    radius: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+};
+
 struct FluidParams {
     gaussian_count: u32,
     dt: f32,
     elapsed: f32,
-    _pad0: f32,
+    // Chance per second that a particle stuck past `respawn_after` is recycled; 0 disables
+    respawn_rate: f32,
     bounds_min: vec2<f32>,
     bounds_max: vec2<f32>,
     damping: f32,
     speed_limit: f32,
     swirl_strength: f32,
-    _pad1: f32,
+    // Seconds a particle must stay pressed against the bounds before it may respawn
+    respawn_after: f32,
     force: vec2<f32>,
+    obstacle_count: u32,
+    _pad2: f32,
+    obstacles: array<FluidObstacle, 8>,
 };
 @group(0) @binding(0) var<uniform> params: FluidParams;
 
 @group(1) @binding(0) var<storage, read_write> out_position_visibility: array<vec4<f32>>;
-@group(2) @binding(0) var<storage, read_write> velocities: array<vec2<f32>>;
+// xy velocity, z seconds spent against the bounds, w unused
+@group(2) @binding(0) var<storage, read_write> velocities: array<vec4<f32>>;
+
+fn hash_u32(x: u32) -> u32 {
+    var h = x;
+    h ^= h >> 16u;
+    h *= 0x7feb352du;
+    h ^= h >> 15u;
+    h *= 0x846ca68bu;
+    h ^= h >> 16u;
+    return h;
+}
+
+fn hash_unit(x: u32) -> f32 {
+    return f32(hash_u32(x) >> 8u) / 16777216.0;
+}
+
+fn obstacle_distance(o: FluidObstacle, p: vec2<f32>) -> f32 {
+    let q = abs(p - o.center) - o.half_extents;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - o.radius;
+}
+
+fn obstacle_normal(o: FluidObstacle, p: vec2<f32>) -> vec2<f32> {
+    let e = vec2<f32>(1e-3, 0.0);
+    let g = vec2<f32>(
+        obstacle_distance(o, p + e.xy) - obstacle_distance(o, p - e.xy),
+        obstacle_distance(o, p + e.yx) - obstacle_distance(o, p - e.yx),
+    );
+    let len = length(g);
+    if (len < 1e-6) { return normalize(p - o.center + vec2<f32>(1e-4, 0.0)); }
+    return g / len;
+}
 
 @compute @workgroup_size(256, 1, 1)
 fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
@@ -23,7 +69,9 @@ fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
 
     var pv = out_position_visibility[i];
     var p = pv.xyz;
-    var v = velocities[i];
+    let state = velocities[i];
+    var v = state.xy;
+    var stuck = state.z;
 
     let r = vec2<f32>(p.x, p.y);
     let rlen = max(length(r), 1e-3);
@@ -47,8 +95,36 @@ fn cs_main(@builtin(global_invocation_id) gid: vec3<u32>) {
     if (p.y < bmin.y) { p.y = bmin.y; v.y = -v.y * bounce; }
     if (p.y > bmax.y) { p.y = bmax.y; v.y = -v.y * bounce; }
 
+    // Recycle particles that stay clumped at the bounds to a random interior position
+    let at_bounds = any(p.xy <= bmin) || any(p.xy >= bmax);
+    stuck = select(0.0, stuck + params.dt, at_bounds);
+    if (stuck > params.respawn_after && params.respawn_rate > 0.0) {
+        let seed = hash_u32(i ^ hash_u32(bitcast<u32>(params.elapsed)));
+        if (hash_unit(seed) < params.respawn_rate * params.dt) {
+            let t = vec2<f32>(hash_unit(seed + 1u), hash_unit(seed + 2u));
+            let inner = mix(bmin, bmax, 0.1 + 0.8 * t);
+            p.x = inner.x;
+            p.y = inner.y;
+            v = vec2<f32>(0.0);
+            stuck = 0.0;
+        }
+    }
+
+    // Push particles out of obstacles and reflect the inward part of their velocity
+    for (var k = 0u; k < min(params.obstacle_count, 8u); k++) {
+        let o = params.obstacles[k];
+        let d = obstacle_distance(o, p.xy);
+        if (d < 0.0) {
+            let n = obstacle_normal(o, p.xy);
+            p.x -= n.x * d;
+            p.y -= n.y * d;
+            let vn = dot(v, n);
+            if (vn < 0.0) { v -= (1.0 + bounce) * vn * n; }
+        }
+    }
+
     v *= params.damping;
 
     out_position_visibility[i] = vec4<f32>(p, pv.w);
-    velocities[i] = v;
+    velocities[i] = vec4<f32>(v, stuck, 0.0);
 }
@@ -1,3 +1,6 @@
+// Degree 0 SH basis constant (gaussian::color::SH_C0)
+const SH_C0: f32 = 0.2820948;
+
 // Spherical harmonics data for a single Gaussian.
 // This must match the layout expected by bevy_gaussian_splatting: array<f32, 48>
 // where 48 = SH_COEFF_COUNT (16 coefficients per channel * 3 channels)
@@ -10,6 +13,20 @@ struct SphericalHarmonic {
 @group(0) @binding(0) var<storage, read>    positions:     array<vec4<f32>>;
 @group(0) @binding(1) var<storage, read>    indices:       array<u32>;
 
+// Per-cloud constants (must match Counts in queue_tri_to_splat_inputs)
+struct TriToSplatCounts {
+    verts:          u32,
+    indices:        u32,
+    tris:           u32,
+    layers:         u32,
+    shell_offset:   f32,
+    // Splats at this index and above are written invisible (progressive reveal)
+    visible:        u32,
+    _pad1:          u32,
+    _pad2:          u32,
+}
+@group(0) @binding(3) var<uniform> counts: TriToSplatCounts;
+
 // Per-view params (dynamic uniform)
 struct TriToSplatParams {
     gaussian_count:   u32,
@@ -105,11 +122,27 @@ fn fade3(t: vec3<f32>) -> vec3<f32> {
     return t * t * (3.0 - 2.0 * t);
 }
 
+// gaussian::rng::hash_u32 (lowbias32)
+fn hash_u32(x: u32) -> u32 {
+    var h = x;
+    h ^= h >> 16u;
+    h *= 0x7feb352du;
+    h ^= h >> 15u;
+    h *= 0x846ca68bu;
+    h ^= h >> 16u;
+    return h;
+}
+
+// Top 24 bits of a hash mapped to [0, 1)
+fn hash_unit(h: u32) -> f32 {
+    return f32(h >> 8u) / 16777216.0;
+}
+
 fn hash31(i: vec3<i32>) -> f32 {
-    // Hash a 3D integer grid point to [-1, 1]
-    let p = vec3<f32>(i);
-    let h = sin(dot(p, vec3<f32>(127.1, 311.7, 74.7))) * 43758.5453;
-    return fract(h) * 2.0 - 1.0;
+    // Hash a 3D integer grid point to [-1, 1], like gaussian::noise's 3D lattice values
+    let c = bitcast<vec3<u32>>(i);
+    let xy = hash_u32(c.y ^ hash_u32(c.x));
+    return hash_unit(hash_u32(c.z ^ hash_u32(xy))) * 2.0 - 1.0;
 }
 
 fn value_noise3d(p: vec3<f32>) -> f32 {
@@ -153,16 +186,10 @@ fn noise_vec3(p: vec3<f32>) -> vec3<f32> {
     return vec3<f32>(nx, ny, nz);
 }
 
-// Hash helpers for deterministic per-triangle randomness
-fn hash11(n: f32) -> f32 {
-    return fract(sin(n * 17.0 + 0.1) * 43758.5453123);
-}
-
-fn hash21(n: f32) -> vec2<f32> {
-    return vec2<f32>(
-        hash11(n + 1.3),
-        hash11(n + 7.5),
-    );
+// Two independent uniform values in [0, 1) per index, for deterministic per-splat randomness
+fn hash21(n: u32) -> vec2<f32> {
+    let x = hash_u32(n);
+    return vec2<f32>(hash_unit(x), hash_unit(hash_u32(x)));
 }
 
 // Rotate vector by quaternion q (x,y,z,w) assuming q is normalized
@@ -173,12 +200,22 @@ fn rotate_vec_by_quat(v: vec3<f32>, q: vec4<f32>) -> vec3<f32> {
 
 
 
-@compute @workgroup_size(64, 1, 1)
+// Injected by TriToSplatPipeline from its `workgroup_size`, which also drives the dispatch
+@compute @workgroup_size(#{WORKGROUP_SIZE}, 1, 1)
 fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 
-    let tri_idx = global_id.x;
+    // One invocation per output splat; shell layers are stacked after each other
+    let splat_idx = global_id.x;
+    let layers = max(counts.layers, 1u);
+
+    // Guard against out-of-bounds access if the number of splats isn't a multiple of the workgroup size.
+    if (counts.tris == 0u || splat_idx >= counts.tris * layers) {
+        return;
+    }
+
+    let tri_idx = splat_idx % counts.tris;
+    let layer   = splat_idx / counts.tris;
 
-    // Guard against out-of-bounds access if the number of triangles isn't a multiple of the workgroup size.
     let indices_len = arrayLength(&indices);
     if (tri_idx * 3u >= indices_len) {
         return;
@@ -194,12 +231,13 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 
 
     // --- Calculate Splat Properties (targets) ---
-    let center = (p0 + p1 + p2) / 3.0;
-
     let v0 = p1 - p0;
     let v1 = p2 - p0;
     let normal = normalize(cross(v0, v1));
 
+    // Shell layers sit at multiples of the offset along the face normal
+    let center = (p0 + p1 + p2) / 3.0 + normal * (f32(layer) * counts.shell_offset);
+
 
     // Simple rotation to align z-axis with the triangle normal
     let base_rotation = quat_from_unit_vectors(vec3<f32>(0.0, 0.0, 1.0), normal);
@@ -211,7 +249,7 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let target_scale_z = 0.01; // Surfel thickness - TODO: Use the MeshToGaussian component for this
 
     // --- Time-based interpolation from origin singularity ---
-    // Looping time base: 5-second cycle
+    // Looping time base: 5-second cycle, MORPH_CYCLE_SECONDS on the CPU side
     let cycle = 5.0;
     let cycle_time = fract(params.elapsed_seconds / cycle) * cycle;
     let duration = max(params.duration_seconds, 0.0001);
@@ -221,7 +259,7 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 
     // Random starting positions sampled on the surface of a sphere (SDF sphere) around the mesh
     // Deterministic per-triangle to keep temporal coherence
-    let uv = hash21(f32(tri_idx));
+    let uv = hash21(splat_idx);
     let z  = 1.0 - 2.0 * uv.x;               // z in [-1,1]
     let a  = 6.28318530718 * uv.y;           // angle
     let r  = sqrt(max(0.0, 1.0 - z * z));
@@ -284,9 +322,10 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
 
 
     // --- Write to Output Buffers ---
-    out_position_visibility[tri_idx]    = vec4<f32>(pos_out, 1.0);
-    out_rotation[tri_idx]               = vec4<f32>(rotation_out.w, rotation_out.x, rotation_out.y, rotation_out.z);
-    out_scale_opacity[tri_idx]          = vec4<f32>(scale_x, scale_y, scale_z, opacity);
+    let visibility = select(0.0, 1.0, splat_idx < counts.visible);
+    out_position_visibility[splat_idx]    = vec4<f32>(pos_out, visibility);
+    out_rotation[splat_idx]               = vec4<f32>(rotation_out.w, rotation_out.x, rotation_out.y, rotation_out.z);
+    out_scale_opacity[splat_idx]          = vec4<f32>(scale_x, scale_y, scale_z, opacity);
 
 
     // --- Per-frame lighting and SH color ---
@@ -298,10 +337,11 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     // Simple Lambert-style shading with no ambient: darker when away from light
     let rgb = base * ndotl;
 
-    // SH DC coefficient encodes color via Y00 = 0.2821
-    let sh_coeff_r = rgb.r / 0.2821;
-    let sh_coeff_g = rgb.g / 0.2821;
-    let sh_coeff_b = rgb.b / 0.2821;
+    // SH DC coefficient encodes color as 0.5 + SH_C0 * dc (gaussian::color::rgb_to_sh_dc)
+    let sh_dc = (rgb - vec3<f32>(0.5)) / SH_C0;
+    let sh_coeff_r = sh_dc.r;
+    let sh_coeff_g = sh_dc.g;
+    let sh_coeff_b = sh_dc.b;
     
     var sh: SphericalHarmonic;
     
@@ -317,5 +357,5 @@ fn cs_main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     sh.coefficients[2] = sh_coeff_b;   // Blue DC term
     
     
-    out_spherical_harmonics[tri_idx] = sh;
+    out_spherical_harmonics[splat_idx] = sh;
 }
\ No newline at end of file
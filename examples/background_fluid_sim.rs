@@ -25,15 +25,76 @@ use bevy_gaussian_splatting::{
 use bevy::render::render_asset::RenderAssets;
 use bevy_gaussian_splatting::gaussian::formats::planar_3d::PlanarStorageGaussian3d;
 
+use bevy_gen_gaussian::gaussian::{
+    color::rgb_to_sh_dc, noise::smooth_value_noise, rng::hash_to_unit, PlanarGaussian3dBuilder,
+};
+
 // ------------------------------- Config ---------------------------------
 
-const NUM_PARTICLES: u32 = 20_000;
 // Make splats more visible for the demo
 const BASE_SCALE: f32 = 0.12;
 // Scale the compute-simulation bounds to better fit the camera view
 const BOUNDS_SCALE_X: f32 = 1.0; // ~20x horizontally
 const BOUNDS_SCALE_Y: f32 = 1.0; // ~12x vertically
 
+/// Particle count and starting state of the fluid cloud. Read once at startup.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct FluidSpawnConfig {
+    pub count: u32,
+    pub distribution: SpawnDistribution,
+    pub velocity: InitialVelocity,
+}
+
+impl Default for FluidSpawnConfig {
+    fn default() -> Self {
+        Self {
+            count: 20_000,
+            distribution: SpawnDistribution::UniformSquare { half_extent: 12.0 },
+            velocity: InitialVelocity::Random { speed: 1.5 },
+        }
+    }
+}
+
+/// Solid shapes inside the bounds that particles bounce off and flow around. Copied into
+/// `FluidParams` every frame, so it can be edited while running; only the first
+/// `MAX_OBSTACLES` are used.
+#[derive(Resource, Clone, Debug)]
+pub struct FluidObstacles(pub Vec<FluidObstacle>);
+
+impl Default for FluidObstacles {
+    fn default() -> Self {
+        Self(vec![
+            FluidObstacle::circle(Vec2::ZERO, 1.5),
+            FluidObstacle::rect(Vec2::new(5.0, 2.5), Vec2::new(1.5, 0.5)),
+            FluidObstacle::rect(Vec2::new(-5.0, -2.5), Vec2::new(0.5, 1.5)),
+        ])
+    }
+}
+
+/// Where particles start on the z = 0 plane, centered on the origin.
+#[derive(Clone, Copy, Debug)]
+pub enum SpawnDistribution {
+    /// Hashed uniformly over `[-half_extent, half_extent]^2`
+    UniformSquare { half_extent: f32 },
+    /// Uniform by area over a disk
+    Disk { radius: f32 },
+    /// Uniform by area between two radii
+    Ring { inner: f32, outer: f32 },
+    /// Row-major square grid with `spacing` between neighbors
+    Grid { spacing: f32 },
+}
+
+/// Starting velocity of each particle.
+#[derive(Clone, Copy, Debug)]
+pub enum InitialVelocity {
+    /// Hashed direction, magnitude between 30% and 100% of `speed`
+    Random { speed: f32 },
+    /// Divergence-free swirl from the curl of 2D value noise sampled at `position * frequency`
+    CurlNoise { speed: f32, frequency: f32 },
+    /// Straight away from the origin; a negative speed points inward
+    Radial { speed: f32 },
+}
+
 // ------------------------------ App entry --------------------------------
 
 fn main() {
@@ -47,6 +108,8 @@ fn main() {
     .add_plugins(bevy::render::extract_component::ExtractComponentPlugin::<FluidCpuInit>::default())
         // Our local plugin that wires the compute before sorting
         .add_plugins(FluidComputePlugin)
+        .init_resource::<FluidSpawnConfig>()
+        .init_resource::<FluidObstacles>()
         .add_systems(Startup, (setup_scene, setup_cloud, setup_ui))
     .add_systems(Update, update_params)
         .run();
@@ -54,7 +117,7 @@ fn main() {
 
 // ------------------------------- Scene -----------------------------------
 
-fn setup_scene(mut commands: Commands) {
+fn setup_scene(mut commands: Commands, config: Res<FluidSpawnConfig>) {
     // 2D overlay camera
     commands.spawn((Camera2d, Camera { order: 10, ..default() }));
 
@@ -73,12 +136,14 @@ fn setup_scene(mut commands: Commands) {
             .looking_at(Vec3::ZERO, Vec3::Y),
         // Initialize with some reasonable defaults; update_params will refresh each frame
         FluidParams {
-            gaussian_count: NUM_PARTICLES,
+            gaussian_count: config.count,
             bounds_min: Vec2::splat(-5.0),
             bounds_max: Vec2::splat(5.0),
             damping: 0.995,
             speed_limit: 5.0,
             swirl_strength: 1.2,
+            respawn_rate: 0.5,
+            respawn_after: 2.0,
             force: Vec2::new(0.0, 0.0),
             ..default()
         },
@@ -99,48 +164,39 @@ fn setup_ui(mut commands: Commands) {
     ));
 }
 
-fn setup_cloud(mut commands: Commands, mut clouds: ResMut<Assets<bevy_gaussian_splatting::PlanarGaussian3d>>) {
+fn setup_cloud(
+    mut commands: Commands,
+    mut clouds: ResMut<Assets<bevy_gaussian_splatting::PlanarGaussian3d>>,
+    config: Res<FluidSpawnConfig>,
+) {
     // Initialize the CPU-side asset with N particles
-    let n = NUM_PARTICLES as usize;
-
-    let mut position_visibility = Vec::with_capacity(n);
-    let mut spherical_harmonic = Vec::with_capacity(n);
-    let mut rotation = Vec::with_capacity(n);
-    let mut scale_opacity = Vec::with_capacity(n);
-
-    // Deterministic hashed distribution in [-12.0, 12.0]^2, z=0 (wider spread for larger bounds)
-    for i in 0..n as u32 {
-        let u = frac(hash11(i as f32));
-        let v = frac(hash11((i as f32) * 1.37 + 7.11));
-        let p = Vec2::new(u * 2.0 - 1.0, v * 2.0 - 1.0) * 12.0; // within [-12, 12]
-        position_visibility.push(PositionVisibility { position: [p.x, p.y, 0.0], visibility: 1.0 });
-
-        spherical_harmonic.push(SphericalHarmonicCoefficients { coefficients: solid_color_dc([0.9, 0.95, 1.0]) });
-        rotation.push(Rotation { rotation: [1.0, 0.0, 0.0, 0.0] });
-        scale_opacity.push(ScaleOpacity { scale: [BASE_SCALE, BASE_SCALE, BASE_SCALE], opacity: 1.0 });
-    }
+    let n = config.count as usize;
 
-    let cloud_asset = bevy_gaussian_splatting::PlanarGaussian3d {
-        position_visibility,
-        spherical_harmonic,
-        rotation,
-        scale_opacity,
-    };
-    let handle = clouds.add(cloud_asset);
-
-    // Create initial velocities (stronger random for visible motion)
-    let mut velocities = Vec::with_capacity(n);
-    for i in 0..n as u32 {
-        let a = 6.2831853 * frac(hash11(i as f32));
-        let r = 0.3 + 0.7 * frac(hash11((i as f32) * 3.1));
-        velocities.push([a.cos() * r * 1.5, a.sin() * r * 1.5]);
+    let mut builder = PlanarGaussian3dBuilder::with_capacity(n);
+    let positions: Vec<Vec2> = (0..config.count).map(|i| spawn_position(config.distribution, i, config.count)).collect();
+
+    for p in &positions {
+        builder.push(
+            PositionVisibility { position: [p.x, p.y, 0.0], visibility: 1.0 },
+            SphericalHarmonicCoefficients { coefficients: solid_color_dc([0.9, 0.95, 1.0]) },
+            Rotation { rotation: [1.0, 0.0, 0.0, 0.0] },
+            ScaleOpacity { scale: [BASE_SCALE, BASE_SCALE, BASE_SCALE], opacity: 1.0 },
+        );
     }
 
+    let handle = clouds.add(builder.finish());
+
+    let velocities = positions
+        .iter()
+        .zip(0..)
+        .map(|(&p, i)| initial_velocity(config.velocity, p, i).extend(0.0).extend(0.0).to_array())
+        .collect();
+
     commands.spawn((
         PlanarGaussian3dHandle(handle),
         CloudSettings { global_scale: 2.0, opacity_adaptive_radius: false, ..default() },
         Name::new("FluidGaussianCloud"),
-        FluidCpuInit { count: NUM_PARTICLES, velocities },
+        FluidCpuInit { count: config.count, velocities },
         Visibility::Visible,
         Transform::IDENTITY,
     ));
@@ -148,20 +204,56 @@ fn setup_cloud(mut commands: Commands, mut clouds: ResMut<Assets<bevy_gaussian_s
 
 // ------------------------------- CPU helpers ------------------------------
 
+fn spawn_position(distribution: SpawnDistribution, i: u32, count: u32) -> Vec2 {
+    // Deterministic hashed samples, so every run starts the same
+    let u = hash_to_unit(0, i);
+    let v = hash_to_unit(1, i);
+    let angle = std::f32::consts::TAU * v;
+
+    match distribution {
+        SpawnDistribution::UniformSquare { half_extent } => Vec2::new(u * 2.0 - 1.0, v * 2.0 - 1.0) * half_extent,
+        SpawnDistribution::Disk { radius } => Vec2::from_angle(angle) * radius * u.sqrt(),
+        SpawnDistribution::Ring { inner, outer } => {
+            let r = (inner * inner).lerp(outer * outer, u).max(0.0).sqrt();
+            Vec2::from_angle(angle) * r
+        }
+        SpawnDistribution::Grid { spacing } => {
+            let side = (count as f32).sqrt().ceil().max(1.0) as u32;
+            let cell = Vec2::new((i % side) as f32, (i / side) as f32);
+            (cell - Vec2::splat((side - 1) as f32 * 0.5)) * spacing
+        }
+    }
+}
+
+fn initial_velocity(velocity: InitialVelocity, position: Vec2, i: u32) -> Vec2 {
+    match velocity {
+        InitialVelocity::Random { speed } => {
+            let a = std::f32::consts::TAU * hash_to_unit(2, i);
+            let r = 0.3 + 0.7 * hash_to_unit(3, i);
+            Vec2::from_angle(a) * r * speed
+        }
+        InitialVelocity::CurlNoise { speed, frequency } => {
+            // Rotated gradient of the noise potential; dividing by the frequency keeps the
+            // magnitude independent of it
+            let eps = 0.01;
+            let p = position * frequency;
+            let dx = smooth_value_noise(p + Vec2::X * eps) - smooth_value_noise(p - Vec2::X * eps);
+            let dy = smooth_value_noise(p + Vec2::Y * eps) - smooth_value_noise(p - Vec2::Y * eps);
+            Vec2::new(dy, -dx) / (2.0 * eps) * speed
+        }
+        InitialVelocity::Radial { speed } => position.normalize_or_zero() * speed,
+    }
+}
+
+
 fn hammersley_1d(i: u32, n: u32) -> f32 { (i as f32 + 0.5) / n as f32 }
 fn reverse_bits(x: u32) -> u32 { x.reverse_bits() }
-fn frac(x: f32) -> f32 { x - x.floor() }
 fn solid_color_dc(rgb: [f32; 3]) -> [f32; 48] {
     let mut c = [0.0_f32; 48];
-    let inv_y00 = 1.0 / 0.2821_f32;
-    c[0] = rgb[0] * inv_y00;
-    c[1] = rgb[1] * inv_y00;
-    c[2] = rgb[2] * inv_y00;
+    c[..3].copy_from_slice(&rgb_to_sh_dc(rgb));
     c
 }
 
-fn hash11(n: f32) -> f32 { (n * 17.0 + 0.1).sin() * 43758.5453_f32 } // not truly random; good enough
-
 // ------------------------------ Params (uniform) ---------------------------
 
 #[derive(Component, Clone, Copy, Default, ExtractComponent, ShaderType)]
@@ -169,14 +261,44 @@ pub struct FluidParams {
     pub gaussian_count: u32,
     pub dt: f32,
     pub elapsed: f32,
-    pub padding0: f32,
+    /// Chance per second that a particle stuck at the bounds for `respawn_after` seconds is
+    /// moved back to a random interior position. 0 disables respawning.
+    pub respawn_rate: f32,
     pub bounds_min: Vec2,
     pub bounds_max: Vec2,
     pub damping: f32,
     pub speed_limit: f32,
     pub swirl_strength: f32,
-    pub padding1: f32,
+    pub respawn_after: f32,
     pub force: Vec2,
+    pub obstacle_count: u32,
+    pub padding2: f32,
+    pub obstacles: [FluidObstacle; MAX_OBSTACLES],
+}
+
+/// Obstacles the uniform has room for.
+pub const MAX_OBSTACLES: usize = 8;
+
+/// A rounded rectangle in the simulation plane: `half_extents` of zero gives a circle of
+/// `radius`, a `radius` of zero a sharp rectangle.
+#[derive(Clone, Copy, Debug, Default, ShaderType)]
+pub struct FluidObstacle {
+    pub center: Vec2,
+    pub half_extents: Vec2,
+    pub radius: f32,
+    pub padding0: f32,
+    pub padding1: f32,
+    pub padding2: f32,
+}
+
+impl FluidObstacle {
+    pub fn circle(center: Vec2, radius: f32) -> Self {
+        Self { center, radius, ..default() }
+    }
+
+    pub fn rect(center: Vec2, half_extents: Vec2) -> Self {
+        Self { center, half_extents, ..default() }
+    }
 }
 
 pub type FluidParamsIndex = DynamicUniformIndex<FluidParams>;
@@ -184,7 +306,8 @@ pub type FluidParamsIndex = DynamicUniformIndex<FluidParams>;
 #[derive(Component, Clone, ExtractComponent)]
 pub struct FluidCpuInit {
     pub count: u32,
-    pub velocities: Vec<[f32; 2]>,
+    /// Per particle: velocity, seconds stuck at the bounds, unused
+    pub velocities: Vec<[f32; 4]>,
 }
 
 #[derive(Component)]
@@ -332,8 +455,8 @@ fn fluid_queue_new(
     if q.is_empty() { return; }
 
     for (e, _planar_rw, cpu) in &q {
-        // Upload velocities as vec2<f32> storage
-        let bytes = bytemuck::cast_slice::<[f32; 2], u8>(&cpu.velocities);
+        // Upload velocities and stuck timers as vec4<f32> storage
+        let bytes = bytemuck::cast_slice::<[f32; 4], u8>(&cpu.velocities);
         let buf = rd.create_buffer_with_data(&BufferInitDescriptor {
             label: Some("fluid.velocities"),
             contents: bytes,
@@ -362,7 +485,7 @@ fn fluid_enqueue_jobs(
         queue.jobs.push((planar.bind_group.clone(), gpu.bind_group_vel.clone(), gpu.workgroups));
     }
     if !queue.jobs.is_empty() {
-        bevy::log::info!("Fluid: enqueued {} job(s)", queue.jobs.len());
+        bevy::log::trace!("Fluid: enqueued {} job(s)", queue.jobs.len());
     }
 }
 
@@ -391,7 +514,7 @@ fn fluid_make_planar_rw_bind_group(
         commands.entity(entity).insert(PlanarStorageBindGroupRw { bind_group: bg });
         created += 1;
     }
-    if created > 0 { bevy::log::info!("Fluid: created {created} planar RW bind group(s)"); }
+    if created > 0 { bevy::log::trace!("Fluid: created {created} planar RW bind group(s)"); }
 }
 
 // ------------------------------- Node -------------------------------------
@@ -415,9 +538,9 @@ impl ViewNode for FluidNode {
     ) -> Result<(), bevy::render::render_graph::NodeRunError> {
         let params_index = view;
         let queue = world.resource::<FluidJobQueue>();
-        bevy::log::info!("FluidNode: run() — jobs={} (will dispatch if > 0)", queue.jobs.len());
+        bevy::log::trace!("FluidNode: run() — jobs={} (will dispatch if > 0)", queue.jobs.len());
         if queue.jobs.is_empty() { return Ok(()); }
-        bevy::log::info!("FluidNode: dispatching {} job(s)", queue.jobs.len());
+        bevy::log::trace!("FluidNode: dispatching {} job(s)", queue.jobs.len());
         let cache = world.resource::<PipelineCache>();
         let pipe = world.resource::<FluidPipeline>();
         let Some(pipeline) = cache.get_compute_pipeline(pipe.pipeline) else { return Ok(()); };
@@ -504,6 +627,7 @@ impl Plugin for FluidComputePlugin {
 fn update_params(
     mut q_cam: Query<(&GlobalTransform, &mut FluidParams, &Projection), With<Camera3d>>, 
     time: Res<Time>,
+    obstacles: Res<FluidObstacles>,
     windows: Query<&Window>,
     mut gizmos: Gizmos,
 ) {
@@ -543,6 +667,20 @@ fn update_params(
     gizmos.line(c, d, col);
     gizmos.line(d, a, col);
 
+    // Obstacles, outlined in the same color
+    params.obstacle_count = obstacles.0.len().min(MAX_OBSTACLES) as u32;
+    for (slot, obstacle) in params.obstacles.iter_mut().zip(&obstacles.0) {
+        *slot = *obstacle;
+
+        let center = obstacle.center.extend(z);
+        let size = obstacle.half_extents * 2.0;
+        if size == Vec2::ZERO {
+            gizmos.circle(Isometry3d::from_translation(center), obstacle.radius, col);
+        } else {
+            gizmos.rect(Isometry3d::from_translation(center), size + Vec2::splat(obstacle.radius * 2.0), col);
+        }
+    }
+
     // Animated swirl around origin
     let t = params.elapsed;
     let swirl = 1.0_f32 + 0.6_f32 * (0.35_f32 * t).sin();
@@ -11,12 +11,17 @@
 //! Controls:
 //! - WASD: Orbit camera around the model
 //! - Q/E: Zoom in and out
+//! - H: Toggle the source mesh for comparison
+//! - F: Toggle a wireframe of the source mesh over the splats
 
 use bevy::prelude::*;
 
 use bevy_gaussian_splatting::{ GaussianCamera };
 use bevy::ui::Val::Px;
-use bevy_gen_gaussian::{GenGaussianPlugin, MeshToGaussian, MeshToGaussianMode, TriToSplatParams};
+use bevy_gen_gaussian::{
+    ComputeStats, GenGaussianPlugin, MeshToGaussian, MeshToGaussianBackend, MeshToGaussianCloud, MeshToGaussianMode,
+    MeshSelection, TriToSplatParams,
+};
 
 /// Path to the mesh asset to convert
 const MESH_PATH: &str = "scenes/FusedFace_LOD3.glb";
@@ -30,7 +35,7 @@ const MESH_PATH: &str = "scenes/FusedFace_LOD3.glb";
 fn main() {
     App::new()
         .add_plugins(DefaultPlugins)
-        .add_plugins(GenGaussianPlugin)
+        .add_plugins(GenGaussianPlugin::default())
 
         .add_systems(Startup,
             (
@@ -42,6 +47,8 @@ fn main() {
 
         .add_systems(Update, (
             camera_controls,
+            toggle_source_mesh,
+            toggle_source_wireframe,
             update_info_text,
         ))
 
@@ -113,9 +120,6 @@ fn setup_scene(mut commands: Commands) {
         Transform::from_xyz(2.0, 4.0, 2.0)
             .looking_at(Vec3::ZERO, Vec3::Y),
     ));
-
-    // Make CPU-side sort trigger more responsive
-    commands.insert_resource(bevy_gaussian_splatting::sort::SortConfig { period_ms: 16 });
 }
 
 
@@ -166,12 +170,17 @@ fn load_mesh(
         SceneRoot(scene),
         Transform::default(),
         Visibility::Visible,
-        MeshToGaussian {
-            mode:               MeshToGaussianMode::TrianglesOneToOne,
-            surfel_thickness:   0.01,
-            hide_source_mesh:   true,
-            realtime:           false,
-        },
+        MeshToGaussian::builder()
+            .mode(MeshToGaussianMode::TrianglesOneToOne)
+            .backend(MeshToGaussianBackend::Gpu)
+            .surfel_thickness(0.01)
+            .hide_source_mesh(true)
+            .mesh_selection(MeshSelection::All)
+            // Keep sorting responsive while the camera moves
+            .sort_period_ms(16)
+            // Build the cloud up over a few seconds instead of popping in
+            .reveal_rate(20_000.0)
+            .build(),
     ));
 }
 
@@ -248,20 +257,78 @@ fn camera_controls(
 
 
 
+/// Toggle the original mesh on and off to compare it against the splat cloud
+fn toggle_source_mesh(
+    mut source_query:   Query<&mut MeshToGaussian>,
+    input:              Res<ButtonInput<KeyCode>>,
+) {
+
+    if !input.just_pressed(KeyCode::KeyH) {
+        return;
+    }
+
+    for mut config in &mut source_query {
+        config.hide_source_mesh = !config.hide_source_mesh;
+    }
+}
+
+
+
+
+
+
+
+/// Toggle a wireframe of the original mesh, to check how the splats line up with its triangles
+fn toggle_source_wireframe(
+    mut source_query:   Query<&mut MeshToGaussian>,
+    input:              Res<ButtonInput<KeyCode>>,
+) {
+
+    if !input.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+
+    for mut config in &mut source_query {
+        config.debug_show_source_wireframe = !config.debug_show_source_wireframe;
+    }
+}
+
+
+
+
+
+
+
 /// Update the UI text showing controls and current state
 fn update_info_text(
     mut text_query: Query<&mut Text, With<InfoText>>,
+    cloud_query:    Query<&MeshToGaussianCloud>,
+    compute_stats:  Res<ComputeStats>,
 ) {
 
     let Ok(mut text) = text_query.single_mut() else { return };
 
+    let status = if compute_stats.pipeline_failed {
+        "Compute pipeline failed to compile, see the log".to_string()
+    } else if !compute_stats.pipeline_ready {
+        format!("Compiling compute pipeline... ({} frames)", compute_stats.frames_waiting_for_pipeline)
+    } else if cloud_query.is_empty() {
+        "Converting...".to_string()
+    } else {
+        let splats: u32 = cloud_query.iter().map(|cloud| cloud.splat_count).sum();
+        format!("Splats: {}", splats)
+    };
+
     **text = format!(
         "Mesh to Gaussian Splats Demo\n\
+        {}\n\
         \n\
         Camera Controls:\n\
         • WASD: Orbit camera\n\
         • Q/E: Zoom in/out\n\
+        • H: Toggle source mesh\n\
+        • F: Toggle source wireframe\n\
     ",
-
+        status,
     );
 }
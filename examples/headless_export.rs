@@ -0,0 +1,89 @@
+//! # Headless Generation
+//!
+//! Generates gaussian clouds on the CPU without a window or GPU and writes them to a PLY file,
+//! the way a command line tool would.
+//!
+//! The generation functions (`mesh_to_gaussians`, `sdf_to_gaussians`, the `cpu_transform`
+//! helpers and `export_cloud_ply`) are plain functions and need no plugin at all. The app here
+//! only exists to show that `GenGaussianPlugin::without_renderer()` runs on `MinimalPlugins`,
+//! for tools that want the conversion systems or the cloud assets.
+//!
+//! The mesh is built in code, since loading glTF files needs the render-side asset types.
+//!
+//! Usage: `cargo run --example headless_export -- [output.ply]`
+
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+use bevy_gaussian_splatting::PlanarGaussian3d;
+use bevy_gen_gaussian::{
+    combine_clouds, compute_stats, export_cloud_ply, mesh_to_gaussians_with_normals, to_planar,
+    GenGaussianPlugin, MeshConversionSettings,
+    sdf_module::{sdf_to_gaussians_with_normals, SDFConversionSettings, SphereSDF},
+};
+
+/// Where the cloud is written when no path is given
+const DEFAULT_OUTPUT: &str = "headless_export.ply";
+
+
+
+
+
+
+
+fn main() {
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_once()),
+            LogPlugin::default(),
+            AssetPlugin::default(),
+            GenGaussianPlugin::without_renderer(),
+        ))
+
+        .add_systems(Startup, generate_and_export)
+
+        .run();
+}
+
+
+
+
+
+
+
+fn generate_and_export(
+    mut clouds: ResMut<Assets<PlanarGaussian3d>>,
+) {
+
+    let output = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_OUTPUT.to_string());
+
+    // A torus from Bevy's primitives, converted splat by splat
+    let mesh                        = Torus::new(0.6, 1.0).mesh().build();
+    let (from_mesh, mesh_normals)   = mesh_to_gaussians_with_normals(
+        &mesh,
+        Transform::from_xyz(-1.5, 0.0, 0.0),
+        &MeshConversionSettings { with_normals: true, ..default() },
+    );
+
+    // A sphere SDF, sampled on a grid
+    let sdf                         = SphereSDF { center: Vec3::new(1.5, 0.0, 0.0), radius: 0.8 };
+    let (from_sdf, sdf_normals)     = sdf_to_gaussians_with_normals(&sdf, &SDFConversionSettings {
+        bounds_min:     Vec3::new(0.5, -1.0, -1.0),
+        bounds_max:     Vec3::new(2.5, 1.0, 1.0),
+        cell_size:      0.05,
+        with_normals:   true,
+        ..default()
+    });
+
+    let gaussians   = combine_clouds(&[&from_mesh, &from_sdf]);
+    let normals     = [mesh_normals.unwrap_or_default(), sdf_normals.unwrap_or_default()].concat();
+
+    match export_cloud_ply(&output, &gaussians, Some(&normals)) {
+        Ok(())  => info!("Wrote {} splats to {output}", gaussians.len()),
+        Err(e)  => error!("Failed to write {output}: {e}"),
+    }
+
+    let stats = compute_stats(&to_planar(&gaussians));
+    info!("Bounds {:?}..{:?}, mean scale {:?}", stats.bounds_min, stats.bounds_max, stats.mean_scale);
+
+    // The cloud is an ordinary asset, ready for anything else the tool does with it
+    clouds.add(to_planar(&gaussians));
+}
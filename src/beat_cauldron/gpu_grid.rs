@@ -0,0 +1,445 @@
+//! GPU generation of the Beat Cauldron grid, writing **directly** into the planar 3D storage.
+//!
+//! - The cloud is spawned zeroed at the right size; a compute pass fills it from a small
+//!   uniform of grid parameters every frame, so edits to `BeatCauldronSettings` show up live.
+//! - The pipeline is only built when `BeatCauldronSettings::gpu_generation` is on at startup.
+//! - Reuses the planar RW bind group from `queue_planar_cloud_rw_bind_group` (and its layout).
+//! - Pipeline layout = [ params_layout (set 0), planar_rw_layout (set 1) ].
+//!
+//! Make sure you load the shader as "beat_cauldron_grid.wgsl" in your assets.
+
+use bevy::{
+    core_pipeline::core_3d::graph::Core3d,
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice, RenderQueue},
+        Render, RenderSet,
+    },
+};
+
+use bevy_gaussian_splatting::{
+    gaussian::f32::{PositionVisibility, Rotation, ScaleOpacity},
+    sort::radix::RadixSortLabel,
+    PlanarGaussian3d,
+    PlanarGaussian3dHandle,
+    SphericalHarmonicCoefficients,
+};
+
+use crate::gaussian::gpu_mesh_to_gaussians::{
+    init_tri_to_splat_pipeline,
+    queue_planar_cloud_rw_bind_group,
+    splatting_render_app,
+    PlanarStorageBindGroupRw,
+    TriToSplatPipeline,
+};
+
+use super::{
+    BeatCauldronSettings,
+    COLOR_NOISE_FREQUENCY,
+    COLOR_NOISE_GAIN,
+    COLOR_NOISE_LACUNARITY,
+    COLOR_NOISE_OCTAVES,
+    COLOR_NOISE_OFFSET,
+    COLOR_NOISE_SEED,
+};
+
+
+
+
+
+
+
+// ------------------------ Grid params (uniform @set(0)) ------------------------
+
+/// Grid parameters visible to the compute shader, mirrored from `BeatCauldronSettings`.
+/// Lives on the cloud entity; its presence is what opts a cloud into GPU generation.
+#[derive(Component, Clone, Copy, Default, ExtractComponent, ShaderType)]
+pub struct BeatCauldronGridParams {
+    pub grid_width:             u32,
+    pub grid_height:            u32,
+    pub noise_octaves:          u32,
+    pub color_noise_octaves:    u32,
+    pub cell_spacing:           Vec2,
+    pub half_extents:           Vec2,
+    pub noise_offset:           Vec2,
+    pub color_noise_frequency:  Vec2,
+    pub color_noise_offset:     Vec2,
+    pub noise_base_frequency:   f32,
+    pub noise_lacunarity:       f32,
+    pub noise_persistence:      f32,
+    pub color_noise_lacunarity: f32,
+    pub color_noise_gain:       f32,
+    pub color_noise_seed:       u32,
+    pub grid_plane_z:           f32,
+    pub altitude_variation:     f32,
+    pub scale_multiplier:       f32,
+    pub opacity_base:           f32,
+    pub min_scale:              Vec3,
+    pub opacity_variation:      f32,
+    pub max_scale:              Vec3,
+    pub _pad:                   f32,
+}
+
+impl BeatCauldronGridParams {
+
+    pub fn from_settings(settings: &BeatCauldronSettings) -> Self {
+        Self {
+            grid_width:             settings.grid_width as u32,
+            grid_height:            settings.grid_height as u32,
+            noise_octaves:          settings.noise_octaves as u32,
+            color_noise_octaves:    COLOR_NOISE_OCTAVES,
+            cell_spacing:           settings.cell_spacing,
+            half_extents:           settings.grid_half_extents(),
+            noise_offset:           settings.noise_offset,
+            color_noise_frequency:  COLOR_NOISE_FREQUENCY,
+            color_noise_offset:     COLOR_NOISE_OFFSET,
+            noise_base_frequency:   settings.noise_base_frequency,
+            noise_lacunarity:       settings.noise_lacunarity,
+            noise_persistence:      settings.noise_persistence,
+            color_noise_lacunarity: COLOR_NOISE_LACUNARITY,
+            color_noise_gain:       COLOR_NOISE_GAIN,
+            color_noise_seed:       COLOR_NOISE_SEED,
+            grid_plane_z:           settings.grid_plane_z,
+            altitude_variation:     settings.altitude_variation,
+            scale_multiplier:       settings.scale_multiplier,
+            opacity_base:           settings.opacity_base,
+            min_scale:              settings.min_scale,
+            opacity_variation:      settings.opacity_variation,
+            max_scale:              settings.max_scale,
+            _pad:                   0.0,
+        }
+    }
+}
+
+
+
+/// A cloud of `count` zeroed gaussians for the compute pass to fill in.
+pub fn zeroed_grid_cloud(
+    count: usize
+) -> PlanarGaussian3d {
+
+    PlanarGaussian3d {
+        position_visibility:    vec![PositionVisibility::default(); count],
+        spherical_harmonic:     vec![SphericalHarmonicCoefficients::default(); count],
+        rotation:               vec![Rotation::default(); count],
+        scale_opacity:          vec![ScaleOpacity::default(); count],
+    }
+}
+
+
+
+/// Keep GPU grid params in sync with `BeatCauldronSettings`, resizing the cloud when the
+/// grid dimensions change.
+pub fn sync_beat_cauldron_grid_params(
+    settings:   Res<BeatCauldronSettings>,
+    mut clouds: ResMut<Assets<PlanarGaussian3d>>,
+    mut q:      Query<(&PlanarGaussian3dHandle, &mut BeatCauldronGridParams)>,
+) {
+
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (handle, mut params) in &mut q {
+        *params = BeatCauldronGridParams::from_settings(&settings);
+
+        let total_splats = settings.total_splats();
+        let needs_resize = clouds
+            .get(&handle.0)
+            .is_some_and(|cloud| cloud.position_visibility.len() != total_splats);
+
+        if needs_resize {
+            if let Some(cloud) = clouds.get_mut(&handle.0) {
+                *cloud = zeroed_grid_cloud(total_splats);
+            }
+        }
+    }
+}
+
+
+
+
+
+
+
+// ---------------- Job Queue (prepared -> consumed) -----------------
+
+#[derive(Clone)]
+struct BeatCauldronGridJob {
+    params_bg:      BindGroup,
+    planar_rw_bg:   BindGroup,
+    workgroups:     UVec3,
+}
+
+#[derive(Resource, Default)]
+pub struct BeatCauldronGridJobQueue {
+    jobs: Vec<BeatCauldronGridJob>,
+}
+
+/// Render-world state of one GPU grid cloud: its params uniform, created once and rewritten in
+/// place every frame, and the bind group pointing at it.
+#[derive(Component)]
+pub struct BeatCauldronGridGpu {
+    uniform:    UniformBuffer<BeatCauldronGridParams>,
+    params_bg:  BindGroup,
+}
+
+/// Rebuild the job list every frame; the grid is regenerated continuously so that buffer
+/// re-uploads (e.g. after a resize) are always filled in.
+fn queue_beat_cauldron_grid_jobs(
+    mut commands:   Commands,
+    rd:             Res<RenderDevice>,
+    rq:             Res<RenderQueue>,
+    pipe:           Res<BeatCauldronGridPipeline>,
+    mut job_queue:  ResMut<BeatCauldronGridJobQueue>,
+    mut q:          Query<(
+        Entity,
+        &PlanarStorageBindGroupRw,
+        &BeatCauldronGridParams,
+        Option<&mut BeatCauldronGridGpu>,
+    )>,
+) {
+
+    job_queue.jobs.clear();
+
+    for (entity, planar_rw, params, gpu) in &mut q {
+        let params_bg = match gpu {
+            Some(mut gpu) => {
+                gpu.uniform.set(*params);
+                gpu.uniform.write_buffer(&rd, &rq);
+                gpu.params_bg.clone()
+            }
+            None => {
+                let mut uniform = UniformBuffer::from(*params);
+                uniform.set_label(Some("beat_cauldron_grid.params"));
+                uniform.write_buffer(&rd, &rq);
+
+                let Some(binding) = uniform.binding() else {
+                    continue;
+                };
+
+                let params_bg = rd.create_bind_group(
+                    "beat_cauldron_grid.params_bg",
+                    &pipe.params_layout,
+                    &[BindGroupEntry {
+                        binding: 0,
+                        resource: binding,
+                    }],
+                );
+
+                commands.entity(entity).insert(BeatCauldronGridGpu { uniform, params_bg: params_bg.clone() });
+                params_bg
+            }
+        };
+
+        // Workgroup sizing: match WGSL @workgroup_size(8, 8, 1), one invocation per cell
+        let workgroups = UVec3::new(
+            params.grid_width.div_ceil(8).max(1),
+            params.grid_height.div_ceil(8).max(1),
+            1,
+        );
+
+        job_queue.jobs.push(BeatCauldronGridJob {
+            params_bg,
+            planar_rw_bg: planar_rw.bind_group.clone(),
+            workgroups,
+        });
+    }
+}
+
+
+
+
+
+
+
+// --------------------------------- Pipeline ----------------------------------
+
+#[derive(Resource)]
+pub struct BeatCauldronGridPipeline {
+    pub pipeline:       CachedComputePipelineId,
+    pub params_layout:  BindGroupLayout, // @group(0)
+}
+
+impl FromWorld for BeatCauldronGridPipeline {
+
+    fn from_world(world: &mut World) -> Self {
+
+        let rd           =  world.resource::<RenderDevice>();
+        let asset_server =  world.resource::<AssetServer>();
+
+        // @group(0): grid params uniform
+        let params_layout = rd.create_bind_group_layout(
+            "beat_cauldron_grid.params_layout",
+            &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: Some(BeatCauldronGridParams::min_size()),
+                },
+                count: None,
+            }],
+        );
+
+        // @group(1): the planar RW layout shared with TriToSplat, so the same bind groups fit
+        let planar_rw_layout = world.resource::<TriToSplatPipeline>().planar_rw_layout.clone();
+
+        let shader: Handle<Shader> = asset_server.load("shaders/beat_cauldron_grid.wgsl");
+
+        let pipeline = world
+            .resource_mut::<PipelineCache>()
+            .queue_compute_pipeline(ComputePipelineDescriptor {
+                label:  Some("beat_cauldron_grid_pipeline".into()),
+                layout: vec![
+                    params_layout.clone(),
+                    planar_rw_layout,
+                ],
+                push_constant_ranges: vec![],
+                shader,
+                shader_defs: vec![],
+                entry_point: "cs_main".into(),
+                zero_initialize_workgroup_memory: false,
+            });
+
+        Self {
+            pipeline,
+            params_layout,
+        }
+    }
+}
+
+
+
+
+
+
+
+// ---------------------------------- Node -------------------------------------
+
+/// The compute node; consumes jobs queued during PrepareBindGroups, before sorting.
+pub struct BeatCauldronGridNode;
+
+impl FromWorld for BeatCauldronGridNode {
+    fn from_world(_world: &mut World) -> Self {
+        Self
+    }
+}
+
+impl ViewNode for BeatCauldronGridNode {
+    type ViewQuery = ();
+
+    fn run(
+        &self,
+        _graph:     &mut RenderGraphContext,
+        rcx:        &mut RenderContext,
+        _view:      QueryItem<Self::ViewQuery>,
+        world:      &World,
+    ) -> Result<(), NodeRunError> {
+
+        // Absent unless GPU generation is on
+        let Some(pipe) = world.get_resource::<BeatCauldronGridPipeline>() else {
+            return Ok(());
+        };
+
+        let cache   = world.resource::<PipelineCache>();
+        let queue   = world.resource::<BeatCauldronGridJobQueue>();
+
+        if queue.jobs.is_empty() {
+            return Ok(());
+        }
+
+        let Some(compute) = cache.get_compute_pipeline(pipe.pipeline) else {
+            return Ok(());
+        };
+
+        let mut pass = rcx
+            .command_encoder()
+            .begin_compute_pass(&ComputePassDescriptor {
+                label: Some("beat_cauldron_grid.compute"),
+                timestamp_writes: None,
+            });
+
+        pass.set_pipeline(compute);
+
+        for job in &queue.jobs {
+            pass.set_bind_group(0, &job.params_bg, &[]);
+            pass.set_bind_group(1, &job.planar_rw_bg, &[]);
+            pass.dispatch_workgroups(job.workgroups.x, job.workgroups.y, job.workgroups.z);
+        }
+
+        Ok(())
+    }
+}
+
+
+
+
+
+
+
+// ------------------------------ Plugin wiring --------------------------------
+
+#[derive(Debug, Hash, PartialEq, Eq, Clone, RenderLabel)]
+pub struct BeatCauldronGridNodeLabel;
+
+/// Render-side half of GPU grid generation. Requires `GenGaussianPlugin` (for the planar RW
+/// bind groups), which `BeatCauldronPlugin` already relies on. Without the splatting renderer
+/// only the main-world params sync is added.
+pub struct BeatCauldronGpuGridPlugin;
+
+impl Plugin for BeatCauldronGpuGridPlugin {
+    fn build(&self, app: &mut App) {
+
+        app.add_plugins(ExtractComponentPlugin::<BeatCauldronGridParams>::default());
+        app.add_systems(Update, sync_beat_cauldron_grid_params);
+
+        let Some(render_app) = splatting_render_app(app) else {
+            return;
+        };
+
+        render_app
+            .init_resource::<BeatCauldronGridJobQueue>()
+            .add_systems(
+                Render,
+                queue_beat_cauldron_grid_jobs
+                    .in_set(RenderSet::PrepareBindGroups)
+                    .after(queue_planar_cloud_rw_bind_group)
+                    .run_if(resource_exists::<BeatCauldronGridPipeline>),
+            )
+            .add_render_graph_node::<ViewNodeRunner<BeatCauldronGridNode>>(Core3d, BeatCauldronGridNodeLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    BeatCauldronGridNodeLabel,
+                    RadixSortLabel,
+                ),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // The grid entity is only spawned with GPU generation on, so skip compiling otherwise
+        let gpu_generation = app
+            .world()
+            .get_resource::<BeatCauldronSettings>()
+            .is_some_and(|settings| settings.gpu_generation);
+
+        if !gpu_generation {
+            return;
+        }
+
+        // Borrows the planar RW layout; no-op if TriToSplatPlugin already created it
+        init_tri_to_splat_pipeline(app);
+
+        if let Some(render_app) = splatting_render_app(app) {
+            render_app.init_resource::<BeatCauldronGridPipeline>();
+        }
+    }
+}
@@ -14,8 +14,23 @@ use bevy_gaussian_splatting::{
     SphericalHarmonicCoefficients,
 };
 
+use crate::gaussian::builder::PlanarGaussian3dBuilder;
+use crate::gaussian::color::rgb_to_sh_dc;
+use crate::gaussian::noise::smooth_value_noise;
 use crate::GenGaussianPlugin;
 
+pub mod gpu_grid;
+
+use gpu_grid::{zeroed_grid_cloud, BeatCauldronGpuGridPlugin, BeatCauldronGridParams};
+
+// Perlin fBm used for the grid brightness, shared by the CPU and GPU generators
+pub(crate) const COLOR_NOISE_SEED: u32 = 1337;
+pub(crate) const COLOR_NOISE_FREQUENCY: Vec2 = Vec2::splat(0.02);
+pub(crate) const COLOR_NOISE_OFFSET: Vec2 = Vec2::new(13.7, -9.1);
+pub(crate) const COLOR_NOISE_OCTAVES: u32 = 4;
+pub(crate) const COLOR_NOISE_LACUNARITY: f32 = 2.0;
+pub(crate) const COLOR_NOISE_GAIN: f32 = 0.5;
+
 #[derive(Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct WorldView;
@@ -60,6 +75,9 @@ pub struct BeatCauldronSettings {
     pub zoom_speed: f32,
     pub min_zoom: f32,
     pub max_zoom: f32,
+    /// Generate the grid in a compute shader instead of on the CPU at startup.
+    /// Edits to these settings are then picked up live.
+    pub gpu_generation: bool,
 }
 
 impl Default for BeatCauldronSettings {
@@ -118,6 +136,7 @@ impl Default for BeatCauldronSettings {
             zoom_speed: 0.05,
             min_zoom: 0.2,
             max_zoom: 6.0,
+            gpu_generation: false,
         }
     }
 }
@@ -185,6 +204,8 @@ impl Plugin for BeatCauldronPlugin {
 
         app.init_resource::<BeatCauldronSettings>();
 
+        app.add_plugins(BeatCauldronGpuGridPlugin);
+
         app.add_systems(Startup, (spawn_world_view_camera, spawn_gaussian_grid));
         app.add_systems(Update, adjust_world_view_zoom);
     }
@@ -203,7 +224,7 @@ pub fn beat_cauldron() {
                 ..default()
             }),
         )
-        .add_plugins((GenGaussianPlugin, BeatCauldronPlugin))
+        .add_plugins((GenGaussianPlugin::default(), BeatCauldronPlugin))
         .run();
 }
 
@@ -261,17 +282,25 @@ fn spawn_gaussian_grid(
 ) {
     use noise::{NoiseFn, Perlin};
 
-    // --- Noise crate config (inline; move to settings later if you want) ---
-    let color_noise_seed: u32 = 1337;
-    let color_noise_freq: Vec2 = Vec2::splat(0.02);
-    let color_noise_offset: Vec2 = Vec2::new(13.7, -9.1);
-    let noise_octaves: u32 = 4;
-    let noise_lacunarity: f32 = 2.0;
-    let noise_gain: f32 = 0.5;
+    if settings.gpu_generation {
+        let handle = clouds.add(zeroed_grid_cloud(settings.total_splats()));
+
+        commands.spawn((
+            PlanarGaussian3dHandle(handle),
+            BeatCauldronGridParams::from_settings(&settings),
+            CloudSettings::default(),
+            Transform::default(),
+            Visibility::Visible,
+            WorldView,
+            Name::new("WorldViewGaussianCloud"),
+        ));
+        return;
+    }
+
     let brightness_gamma: f32 = 1.0;
 
     // Build a single Perlin generator once.
-    let perlin = Perlin::new(color_noise_seed);
+    let perlin = Perlin::new(COLOR_NOISE_SEED);
 
     // Simple Perlin fBm returning [0,1].
     #[inline]
@@ -293,10 +322,7 @@ fn spawn_gaussian_grid(
 
     let total_splats = settings.total_splats();
 
-    let mut positions: Vec<PositionVisibility> = Vec::with_capacity(total_splats);
-    let mut harmonics: Vec<SphericalHarmonicCoefficients> = Vec::with_capacity(total_splats);
-    let mut rotations: Vec<Rotation> = Vec::with_capacity(total_splats);
-    let mut scales: Vec<ScaleOpacity> = Vec::with_capacity(total_splats);
+    let mut builder = PlanarGaussian3dBuilder::with_capacity(total_splats);
 
     let half_extents = settings.grid_half_extents();
 
@@ -314,28 +340,22 @@ fn spawn_gaussian_grid(
             let altitude_noise = settings.sample_noise(grid_position, Vec2::new(17.0, -53.0));
 
             // --- simplified color via Noise crate: grayscale brightness in [0,1] ---
-            let p = grid_position * color_noise_freq + color_noise_offset;
-            let mut brightness = fbm2_perlin(&perlin, p, noise_octaves, noise_lacunarity, noise_gain);
+            let p = grid_position * COLOR_NOISE_FREQUENCY + COLOR_NOISE_OFFSET;
+            let mut brightness = fbm2_perlin(
+                &perlin,
+                p,
+                COLOR_NOISE_OCTAVES,
+                COLOR_NOISE_LACUNARITY,
+                COLOR_NOISE_GAIN,
+            );
             brightness = brightness.powf(brightness_gamma.max(0.01));
 
             let mut sh = SphericalHarmonicCoefficients::default();
-            sh.coefficients[0] = brightness;
-            sh.coefficients[1] = brightness;
-            sh.coefficients[2] = brightness;
+            sh.coefficients[..3].copy_from_slice(&rgb_to_sh_dc([brightness; 3]));
 
             let altitude = settings.grid_plane_z
                 + (altitude_noise * 2.0 - 1.0) * settings.altitude_variation;
 
-            positions.push(PositionVisibility {
-                position: [world_x, world_y, altitude],
-                visibility: 1.0,
-            });
-
-            harmonics.push(sh);
-            rotations.push(Rotation {
-                rotation: [1.0, 0.0, 0.0, 0.0],
-            });
-
             let scale_x = settings.min_scale.x
                 + base_noise * (settings.max_scale.x - settings.min_scale.x);
             let scale_y = settings.min_scale.y
@@ -349,21 +369,24 @@ fn spawn_gaussian_grid(
                 + (opacity_noise * 2.0 - 1.0) * settings.opacity_variation)
                 .clamp(0.0, 1.0);
 
-            scales.push(ScaleOpacity {
-                scale: scale.to_array(),
-                opacity,
-            });
+            builder.push(
+                PositionVisibility {
+                    position: [world_x, world_y, altitude],
+                    visibility: 1.0,
+                },
+                sh,
+                Rotation {
+                    rotation: [1.0, 0.0, 0.0, 0.0],
+                },
+                ScaleOpacity {
+                    scale: scale.to_array(),
+                    opacity,
+                },
+            );
         }
     }
 
-    let cloud_asset = PlanarGaussian3d {
-        position_visibility: positions,
-        spherical_harmonic: harmonics,
-        rotation: rotations,
-        scale_opacity: scales,
-    };
-
-    let handle = clouds.add(cloud_asset);
+    let handle = clouds.add(builder.finish());
 
     commands.spawn((
         PlanarGaussian3dHandle(handle),
@@ -375,27 +398,3 @@ fn spawn_gaussian_grid(
     ));
 }
 
-
-
-fn smooth_value_noise(point: Vec2) -> f32 {
-    let cell = point.floor();
-    let frac = point - cell;
-
-    let c00 = lattice_value(cell);
-    let c10 = lattice_value(cell + Vec2::new(1.0, 0.0));
-    let c01 = lattice_value(cell + Vec2::new(0.0, 1.0));
-    let c11 = lattice_value(cell + Vec2::new(1.0, 1.0));
-
-    let fade = frac * frac * (Vec2::splat(3.0) - 2.0 * frac);
-
-    let nx0 = c00 + (c10 - c00) * fade.x;
-    let nx1 = c01 + (c11 - c01) * fade.x;
-
-    let value = nx0 + (nx1 - nx0) * fade.y;
-    value.clamp(0.0, 1.0)
-}
-
-fn lattice_value(point: Vec2) -> f32 {
-    let dot = point.dot(Vec2::new(127.1, 311.7));
-    (dot.sin() * 43758.5453).fract()
-}
\ No newline at end of file
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use bevy_gaussian_splatting::{PlanarGaussian3d, PlanarGaussian3dHandle};
 
 /// Simple metrics for tracking Gaussian cloud information
 #[derive(Resource, Default, Debug)]
@@ -11,22 +12,72 @@ pub struct GaussianMetrics {
 /// System to update FPS metrics
 pub fn update_metrics(time: Res<Time>, mut metrics: ResMut<GaussianMetrics>) {
     metrics.last_frame_time = time.delta_secs();
-    metrics.fps = 1.0 / time.delta_secs();
+    if metrics.last_frame_time > 0.0 {
+        metrics.fps = 1.0 / metrics.last_frame_time;
+    }
 }
 
-/// System to count gaussians in the scene (placeholder - would need actual implementation)
-pub fn count_gaussians(mut metrics: ResMut<GaussianMetrics>) {
-    // This would need to query for actual Gaussian entities in a real implementation
-    // For now it's just a placeholder
-    metrics.total_gaussians = 0;
+/// System to count the gaussians in every loaded cloud in the scene
+pub fn count_gaussians(
+    mut metrics: ResMut<GaussianMetrics>,
+    clouds: Res<Assets<PlanarGaussian3d>>,
+    q: Query<&PlanarGaussian3dHandle>,
+) {
+    metrics.total_gaussians = q
+        .iter()
+        .filter_map(|handle| clouds.get(&handle.0))
+        .map(|cloud| cloud.position_visibility.len())
+        .sum();
 }
 
 /// Debug overlay system for displaying metrics
 pub fn debug_overlay(
-    mut gizmos: Gizmos,
     metrics: Res<GaussianMetrics>,
 ) {
     // Simple text overlay would go here
     // This is a placeholder for now
     info!("FPS: {:.1}, Gaussians: {}", metrics.fps, metrics.total_gaussians);
 }
+
+/// Logs [`GaussianMetrics`] every `interval_secs`, with no UI, gizmo or render dependency, so
+/// it works in headless apps. The metrics themselves are updated by `GenGaussianPlugin`.
+pub struct MetricsReportPlugin {
+    pub interval_secs: f32,
+}
+
+impl Default for MetricsReportPlugin {
+    fn default() -> Self {
+        Self { interval_secs: 1.0 }
+    }
+}
+
+impl Plugin for MetricsReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GaussianMetrics>();
+        app.insert_resource(MetricsReportTimer(Timer::from_seconds(
+            self.interval_secs.max(0.0),
+            TimerMode::Repeating,
+        )));
+        app.add_systems(Update, report_metrics);
+    }
+}
+
+/// Interval timer for [`report_metrics`]
+#[derive(Resource, Debug)]
+pub struct MetricsReportTimer(pub Timer);
+
+/// System that logs the metrics each time the report timer finishes
+pub fn report_metrics(
+    time: Res<Time>,
+    mut timer: ResMut<MetricsReportTimer>,
+    metrics: Res<GaussianMetrics>,
+) {
+    if timer.0.tick(time.delta()).just_finished() {
+        info!(
+            "Metrics: {:.1} fps ({:.2} ms), {} gaussians",
+            metrics.fps,
+            metrics.last_frame_time * 1000.0,
+            metrics.total_gaussians
+        );
+    }
+}
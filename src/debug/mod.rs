@@ -4,3 +4,20 @@ pub mod metrics;
 
 // Re-export
 pub use metrics::*;
+
+
+
+/// `trace!` for per-frame hot paths. Compiled out unless the `debug_logging` feature is on,
+/// so render-world systems don't pay for formatting even when the filter would drop it.
+macro_rules! hot_trace {
+    ($($arg:tt)*) => {{
+        #[cfg(feature = "debug_logging")]
+        bevy::log::trace!($($arg)*);
+        #[cfg(not(feature = "debug_logging"))]
+        {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+
+pub(crate) use hot_trace;
@@ -0,0 +1,62 @@
+// Incremental construction of planar clouds without juggling four parallel vectors.
+
+use bevy_gaussian_splatting::{
+    gaussian::f32::{PositionVisibility, Rotation, ScaleOpacity},
+    PlanarGaussian3d,
+    SphericalHarmonicCoefficients,
+};
+
+
+
+
+
+
+
+/// Builds a `PlanarGaussian3d` one gaussian at a time.
+///
+/// All four attribute arrays are allocated once up front, so pushing up to `capacity`
+/// gaussians never reallocates and `finish` hands the arrays over without copying.
+#[derive(Debug, Default)]
+pub struct PlanarGaussian3dBuilder {
+    cloud: PlanarGaussian3d,
+}
+
+impl PlanarGaussian3dBuilder {
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cloud: PlanarGaussian3d {
+                position_visibility:    Vec::with_capacity(capacity),
+                spherical_harmonic:     Vec::with_capacity(capacity),
+                rotation:               Vec::with_capacity(capacity),
+                scale_opacity:          Vec::with_capacity(capacity),
+            },
+        }
+    }
+
+    /// Append one gaussian.
+    pub fn push(
+        &mut self,
+        position_visibility:    PositionVisibility,
+        spherical_harmonic:     SphericalHarmonicCoefficients,
+        rotation:               Rotation,
+        scale_opacity:          ScaleOpacity,
+    ) {
+        self.cloud.position_visibility.push(position_visibility);
+        self.cloud.spherical_harmonic.push(spherical_harmonic);
+        self.cloud.rotation.push(rotation);
+        self.cloud.scale_opacity.push(scale_opacity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.cloud.position_visibility.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cloud.position_visibility.is_empty()
+    }
+
+    pub fn finish(self) -> PlanarGaussian3d {
+        self.cloud
+    }
+}
@@ -0,0 +1,66 @@
+// Conversions between display colors and the spherical harmonic DC term. Every generator
+// should go through these so clouds from different paths render with the same colors.
+
+use bevy::math::Vec3;
+
+/// Degree 0 real spherical harmonic basis constant, 1 / (2√π).
+pub const SH_C0: f32 = 0.282_094_8;
+
+/// Degree 1 real spherical harmonic basis constant, √3 / (2√π).
+pub const SH_C1: f32 = 0.488_602_5;
+
+/// Share of a splat's color added when it is seen head-on, and removed when seen from behind,
+/// by the first-order band generators write for `sh_degree >= 1`.
+pub const SH_VIEW_GRADIENT: f32 = 0.2;
+
+
+
+
+
+
+
+/// SH DC coefficients for a linear RGB color, following the 3DGS convention
+/// `color = 0.5 + SH_C0 * dc`.
+pub fn rgb_to_sh_dc(
+    rgb: [f32; 3]
+) -> [f32; 3] {
+
+    rgb.map(|c| (c - 0.5) / SH_C0)
+}
+
+
+
+/// Inverse of [`rgb_to_sh_dc`]: the view-independent color encoded by the DC coefficients.
+pub fn sh_dc_to_rgb(
+    dc: [f32; 3]
+) -> [f32; 3] {
+
+    dc.map(|c| 0.5 + SH_C0 * c)
+}
+
+
+
+/// First-order SH coefficients, interleaved per channel like the DC term (`coefficients[3..12]`),
+/// for a view-dependent brightness gradient: `rgb * strength` is added when the splat is viewed
+/// against `normal`, i.e. its front faces the camera, subtracted when viewed along it, and
+/// blended linearly in between. A zero normal gives no gradient.
+pub fn rgb_to_sh_view_gradient(
+    rgb:        [f32; 3],
+    normal:     Vec3,
+    strength:   f32,
+) -> [f32; 9] {
+
+    // The renderer evaluates -C1·y, C1·z, -C1·x for the view ray from the camera, so these
+    // coefficients sum to -strength · rgb · dot(normal, ray)
+    let n       = normal.normalize_or_zero();
+    let basis   = [n.y, -n.z, n.x].map(|b| b * strength / SH_C1);
+
+    let mut out = [0.0; 9];
+    for (m, b) in basis.into_iter().enumerate() {
+        for (c, channel) in rgb.into_iter().enumerate() {
+            out[m * 3 + c] = b * channel;
+        }
+    }
+
+    out
+}
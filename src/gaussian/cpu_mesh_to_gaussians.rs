@@ -1,9 +1,16 @@
-use std::collections::HashSet;
+use std::collections::HashMap;
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
-use bevy_gaussian_splatting::Gaussian3d;
+use bevy_gaussian_splatting::{material::spherical_harmonics::SH_COEFF_COUNT, Gaussian3d, PlanarGaussian3d};
 
-use crate::gaussian::settings::{MeshConversionSettings, PointCloudSettings};
+use crate::gaussian::settings::{ColorMode, MeshConversionSettings, PointCloudSettings};
+use crate::gaussian::color::{rgb_to_sh_dc, rgb_to_sh_view_gradient, SH_VIEW_GRADIENT};
+use crate::gaussian::cpu_transform::{stored_rotation, to_planar, unit_rotation};
+use crate::gaussian::rng::hash_to_vec3;
+use crate::gaussian::MeshToGaussianMode;
+
+/// Triangles with less area than this produce no splat; their basis would be degenerate
+const MIN_TRIANGLE_AREA: f32 = 1e-10;
 
 
 
@@ -15,18 +22,38 @@ use crate::gaussian::settings::{MeshConversionSettings, PointCloudSettings};
 /// 
 /// This is a pure function that takes a mesh and produces gaussians without side effects.
 /// It can generate gaussians for vertices, edges (connecting vertices), and faces (triangle centers).
+///
+/// The output is deterministic, so generated clouds can be snapshot tested: vertex splats come
+/// first in (welded) vertex order, then face splats in triangle order, then edge splats sorted
+/// by their welded `(lo, hi)` vertex index pair.
 pub fn mesh_to_gaussians(
     mesh:       &Mesh, 
     transform:  Transform, 
     settings:   &MeshConversionSettings
 ) -> Vec<Gaussian3d> {
 
+    mesh_to_gaussians_with_normals(mesh, transform, settings).0
+}
+
+
+
+/// Same as [`mesh_to_gaussians`], but also returns one unit normal per gaussian, rotated by
+/// `transform`, when `settings.with_normals` is set. Pass them to `export_cloud_ply`.
+///
+/// Vertex and edge splats take the (welded) vertex normals, face splats the normal of the
+/// surface they lie on.
+pub fn mesh_to_gaussians_with_normals(
+    mesh:       &Mesh,
+    transform:  Transform,
+    settings:   &MeshConversionSettings
+) -> (Vec<Gaussian3d>, Option<Vec<Vec3>>) {
+
     let topology = mesh.primitive_topology();
     let positions = match read_positions(mesh) {
         Some(v) => v,
         None => {
             warn!("mesh_to_gaussians: mesh missing positions");
-            return Vec::new();
+            return (Vec::new(), settings.with_normals.then(Vec::new));
         }
     };
 
@@ -44,18 +71,46 @@ pub fn mesh_to_gaussians(
         compute_vertex_normals(topology, &positions, indices_u32.as_ref())
     );
 
+    let colors = SplatColors::new(settings.color_mode, &positions);
+
     let mut out: Vec<Gaussian3d> = Vec::new();
 
+    // One world space normal per entry of `out`, dropped at the end unless requested
+    let mut out_normals: Vec<Vec3> = Vec::new();
+
+    // Vertex and edge splats work on welded vertices, so per-face seams don't emit duplicates
+    let welded = if settings.include_vertices || settings.include_edges {
+        weld_vertices(&positions, &vertex_normals, settings.weld_threshold)
+    } else {
+        WeldedVertices::default()
+    };
+
     // 1) Vertices
     if settings.include_vertices {
 
-        for (vpos, vnorm) in positions.iter().zip(vertex_normals.iter()) {
+        for (vpos, vnorm) in welded.positions.iter().zip(welded.normals.iter()) {
+
+            if !vpos.is_finite() {
+                continue;
+            }
 
             let pos     = transform.transform_point(*vpos);
             let rot     = Quat::IDENTITY;
-            let scale   = Vec3::splat(settings.vertex_scale);
-
-            out.push(gaussian_from_transform(pos, rot, scale, *vnorm, settings.opacity));
+            let scale   = clamp_scale(Vec3::splat(settings.vertex_scale), settings);
+
+            let rgb     = colors.rgb(*vnorm, *vpos);
+            let normal  = (transform.rotation * *vnorm).normalize_or_zero();
+
+            out.push(gaussian_from_transform(
+                pos,
+                rot,
+                scale,
+                rgb,
+                settings.opacity,
+                normal,
+                settings.sh_degree,
+            ));
+            out_normals.push(normal);
         }
     }
 
@@ -67,82 +122,127 @@ pub fn mesh_to_gaussians(
 
             let tri_iter = triangles_from(topology, &indices);
 
-            let tris: Vec<[u32; 3]> = tri_iter.collect();
-
-            for tri in &tris {
-                let p0 = positions[tri[0] as usize];
-                let p1 = positions[tri[1] as usize];
-                let p2 = positions[tri[2] as usize];
-
-                let centroid = (p0 + p1 + p2) / 3.0;
-
-                let u = p1 - p0;
-                let v = p2 - p0;
-
-                let x_axis = u.normalize_or_zero();
-                let z_axis = u.cross(v).normalize_or_zero();
-                let y_axis = z_axis.cross(x_axis);
-
-                let rot = Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, z_axis));
+            let all_tris: Vec<[u32; 3]> = tri_iter.collect();
+            let tris: Vec<[u32; 3]> = all_tris
+                .iter()
+                .copied()
+                .filter(|tri| triangle_corners(&positions, *tri).is_some_and(is_valid_triangle))
+                .collect();
+
+            if tris.len() < all_tris.len() {
+                trace!(
+                    "mesh_to_gaussians: skipped {} degenerate or non-finite triangles",
+                    all_tris.len() - tris.len()
+                );
+            }
 
-                let u_len   = u.length();
-                let v_on_y  = v.dot(y_axis).abs();
+            let faces_start = out.len();
 
-                let scale   = Vec3::new(u_len, v_on_y, settings.face_scale);
-                let face_n  = z_axis;
+            match settings.mode {
+                MeshToGaussianMode::TrianglesOneToOne => {
+                    for tri in &tris {
+                        out.push(triangle_gaussian(&positions, *tri, transform, settings, &colors));
+                    }
+                }
+                MeshToGaussianMode::Quads => {
+                    out.extend(quad_gaussians(&positions, &tris, transform, settings, &colors));
+                }
+                MeshToGaussianMode::ThickShell { layers, offset } => {
+                    for tri in &tris {
+                        let surfel = triangle_gaussian(&positions, *tri, transform, settings, &colors);
+                        let normal = transform.rotation * face_normal(
+                            positions[tri[0] as usize],
+                            positions[tri[1] as usize],
+                            positions[tri[2] as usize],
+                        );
+
+                        for layer in 0..layers.max(1) {
+                            let mut g = surfel;
+                            let pos = Vec3::from(surfel.position_visibility.position) + normal * (layer as f32 * offset);
+                            g.position_visibility.position = pos.to_array();
+                            out.push(g);
+                        }
+                    }
+                }
+                MeshToGaussianMode::SubdividedTriangles { levels } => {
+                    for tri in &tris {
+                        subdivided_triangle_gaussians(
+                            &positions,
+                            &vertex_normals,
+                            *tri,
+                            levels,
+                            transform,
+                            settings,
+                            &colors,
+                            &mut out,
+                        );
+                    }
+                }
+            }
 
-                out.push(gaussian_from_transform(
-                    transform.transform_point(centroid),
-                    rot,
-                    scale,
-                    face_n,
-                    settings.opacity,
-                ));
+            // Every face surfel lies in its local XY plane
+            for g in &out[faces_start..] {
+                out_normals.push(transform.rotation * unit_rotation(g) * Vec3::Z);
             }
         }
 
 
-        // 3) Edges: dedupe undirected
+        // 3) Edges: dedupe undirected, in sorted order
         if settings.include_edges {
 
             let tri_iter                = triangles_from(topology, &indices);
             let tris: Vec<[u32; 3]>     = tri_iter.collect();
 
-            let mut edge_set: HashSet<(u32, u32)> = HashSet::new();
-            
-            for tri in &tris {
-
-                let edges = [
-                    (tri[0], tri[1]),
-                    (tri[1], tri[2]),
-                    (tri[2], tri[0]),
-                ];
-
-                for (a, b) in edges {
-                    let (lo, hi) = if a < b { (a, b) } else { (b, a) };
-
-                    if edge_set.insert((lo, hi)) {
-
-                        let pa      = positions[lo as usize];
-                        let pb      = positions[hi as usize];
-                        let mid     = (pa + pb) * 0.5;
-                        let na      = vertex_normals[lo as usize];
-                        let nb      = vertex_normals[hi as usize];
-                        let n       = (na + nb).normalize_or_zero();
-
-                        let edge_vec    = pb - pa;
-                        let rot         = Quat::from_rotation_arc(Vec3::X, edge_vec.normalize_or_zero());
-                        let scale       = Vec3::new(edge_vec.length(), settings.edge_scale, settings.edge_scale);
-
-                        out.push(gaussian_from_transform(
-                            transform.transform_point(mid),
-                            rot,
-                            scale,
-                            n,
-                            settings.opacity,
-                        ));
-                    }
+            // Undirected edges between welded vertices, sorted so the output doesn't depend
+            // on triangle winding or the order triangles share an edge in
+            let mut edges: Vec<(u32, u32)> = tris
+                .iter()
+                .flat_map(|tri| [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])])
+                .map(|(a, b)| (welded.remap[a as usize], welded.remap[b as usize]))
+                // Collapsed by welding
+                .filter(|(a, b)| a != b)
+                .map(|(a, b)| if a < b { (a, b) } else { (b, a) })
+                .collect();
+
+            edges.sort_unstable();
+            edges.dedup();
+
+            for (lo, hi) in edges {
+
+                let pa      = welded.positions[lo as usize];
+                let pb      = welded.positions[hi as usize];
+
+                // Coincident or non-finite endpoints have no direction to align with
+                let length_sq = (pb - pa).length_squared();
+                if !length_sq.is_finite() || length_sq <= f32::EPSILON * f32::EPSILON {
+                    trace!("mesh_to_gaussians: skipped degenerate edge ({lo}, {hi})");
+                    continue;
                 }
+
+                let mid     = (pa + pb) * 0.5;
+                let na      = welded.normals[lo as usize];
+                let nb      = welded.normals[hi as usize];
+                let n       = (na + nb).normalize_or_zero();
+
+                let edge_vec    = pb - pa;
+                let rot         = edge_rotation(edge_vec, n);
+                let scale       = clamp_scale(
+                    Vec3::new(edge_vec.length(), settings.edge_scale, settings.edge_scale),
+                    settings,
+                );
+
+                let normal = (transform.rotation * n).normalize_or_zero();
+
+                out.push(gaussian_from_transform(
+                    transform.transform_point(mid),
+                    rot,
+                    scale,
+                    colors.rgb(n, mid),
+                    settings.opacity,
+                    normal,
+                    settings.sh_degree,
+                ));
+                out_normals.push(normal);
             }
         }
     } else {
@@ -150,7 +250,22 @@ pub fn mesh_to_gaussians(
         debug!("mesh_to_gaussians: mesh had no indices; produced only vertex splats");
     }
 
-    out
+    (out, settings.with_normals.then_some(out_normals))
+}
+
+
+
+
+
+/// Same as [`mesh_to_gaussians`], but returns the planar (struct-of-arrays) cloud the renderer
+/// consumes, ready to be added to `Assets<PlanarGaussian3d>`.
+pub fn mesh_to_planar_gaussians(
+    mesh:       &Mesh,
+    transform:  Transform,
+    settings:   &MeshConversionSettings
+) -> PlanarGaussian3d {
+
+    to_planar(&mesh_to_gaussians(mesh, transform, settings))
 }
 
 
@@ -165,6 +280,24 @@ pub fn points_to_gaussians(
     settings:       &PointCloudSettings,
 ) -> Vec<Gaussian3d> {
 
+    points_to_gaussians_with_colors(positions, normals, None, transform, settings)
+}
+
+
+
+/// [`points_to_gaussians`] with optional per-point RGB colors in 0..1, used when
+/// `settings.color_mode` is `ColorMode::PerPoint`. Points past the end of `colors`, or every
+/// point when `colors` is `None`, are colored by normal instead.
+pub fn points_to_gaussians_with_colors(
+    positions:      &[Vec3],
+    normals:        Option<&[Vec3]>,
+    colors:         Option<&[[f32; 3]]>,
+    transform:      Transform,
+    settings:       &PointCloudSettings,
+) -> Vec<Gaussian3d> {
+
+    let splat_colors = SplatColors::new(settings.color_mode, positions);
+
     let mut out = Vec::new();
     
     for (i, &pos) in positions.iter().enumerate() {
@@ -178,6 +311,11 @@ pub fn points_to_gaussians(
             // Use position as normal if no normals provided
             pos.normalize_or_zero()
         };
+
+        let rgb = match (settings.color_mode, colors.and_then(|c| c.get(i))) {
+            (ColorMode::PerPoint, Some(&rgb))   => rgb,
+            _                                   => splat_colors.rgb(normal, pos),
+        };
         
         let rot = Quat::IDENTITY;
         let scale = Vec3::splat(settings.scale);
@@ -186,8 +324,10 @@ pub fn points_to_gaussians(
             world_pos, 
             rot, 
             scale, 
-            normal, 
-            settings.opacity
+            rgb, 
+            settings.opacity,
+            transform.rotation * normal,
+            settings.sh_degree,
         ));
     }
     
@@ -198,8 +338,301 @@ pub fn points_to_gaussians(
 
 
 
-// Helper function to get triangles from indices based on topology
-fn triangles_from(
+// One surfel per triangle: centered on the centroid, first axis along the first edge.
+fn triangle_gaussian(
+    positions:  &[Vec3],
+    tri:        [u32; 3],
+    transform:  Transform,
+    settings:   &MeshConversionSettings,
+    colors:     &SplatColors,
+) -> Gaussian3d {
+
+    let corners = [
+        positions[tri[0] as usize],
+        positions[tri[1] as usize],
+        positions[tri[2] as usize],
+    ];
+
+    triangle_surfel(corners, None, transform, settings, colors)
+}
+
+
+
+// Surfel spanning three corners. Colored from the face normal unless `color_normal` is given.
+fn triangle_surfel(
+    [p0, p1, p2]:   [Vec3; 3],
+    color_normal:   Option<Vec3>,
+    transform:      Transform,
+    settings:       &MeshConversionSettings,
+    colors:         &SplatColors,
+) -> Gaussian3d {
+
+    let centroid = (p0 + p1 + p2) / 3.0;
+
+    let u = p1 - p0;
+    let v = p2 - p0;
+
+    let x_axis = u.normalize_or_zero();
+    let z_axis = u.cross(v).normalize_or_zero();
+    let y_axis = z_axis.cross(x_axis);
+
+    let rot = Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, z_axis));
+
+    let u_len   = u.length();
+    let v_on_y  = v.dot(y_axis).abs();
+
+    let scale   = clamp_scale(Vec3::new(u_len, v_on_y, settings.face_scale), settings);
+    let face_n  = color_normal.unwrap_or(z_axis);
+
+    gaussian_from_transform(
+        transform.transform_point(centroid),
+        rot,
+        scale,
+        colors.rgb(face_n, centroid),
+        settings.opacity,
+        transform.rotation * face_n,
+        settings.sh_degree,
+    )
+}
+
+
+
+// Recursively splits a triangle at its edge midpoints `levels` times and emits one surfel per
+// leaf. Sub-triangles are tracked in barycentric coordinates so vertex normals can be
+// interpolated at each leaf's centroid.
+#[allow(clippy::too_many_arguments)]
+fn subdivided_triangle_gaussians(
+    positions:      &[Vec3],
+    vertex_normals: &[Vec3],
+    tri:            [u32; 3],
+    levels:         u8,
+    transform:      Transform,
+    settings:       &MeshConversionSettings,
+    colors:         &SplatColors,
+    out:            &mut Vec<Gaussian3d>,
+) {
+
+    // 4^6 = 4096 splats per source triangle is already far past useful density
+    const MAX_LEVELS: u8 = 6;
+
+    if levels == 0 {
+        out.push(triangle_gaussian(positions, tri, transform, settings, colors));
+        return;
+    }
+
+    let p = tri.map(|i| positions[i as usize]);
+    let n = tri.map(|i| vertex_normals.get(i as usize).copied().unwrap_or(Vec3::ZERO));
+
+    let mut stack = vec![([Vec3::X, Vec3::Y, Vec3::Z], levels.min(MAX_LEVELS))];
+
+    while let Some((bary, depth)) = stack.pop() {
+
+        if depth == 0 {
+            let corners = bary.map(|b| p[0] * b.x + p[1] * b.y + p[2] * b.z);
+
+            let center  = (bary[0] + bary[1] + bary[2]) / 3.0;
+            let normal  = (n[0] * center.x + n[1] * center.y + n[2] * center.z).normalize_or_zero();
+
+            // Fall back to the face normal when the mesh has none to interpolate
+            let normal  = (normal != Vec3::ZERO).then_some(normal);
+
+            out.push(triangle_surfel(corners, normal, transform, settings, colors));
+            continue;
+        }
+
+        let [a, b, c] = bary;
+        let ab = (a + b) * 0.5;
+        let bc = (b + c) * 0.5;
+        let ca = (c + a) * 0.5;
+
+        // Same winding as the parent so surfel normals stay consistent
+        stack.push(([a, ab, ca], depth - 1));
+        stack.push(([ab, b, bc], depth - 1));
+        stack.push(([ca, bc, c], depth - 1));
+        stack.push(([ab, bc, ca], depth - 1));
+    }
+}
+
+
+
+// Basis for an edge surfel: x along the edge, z along the endpoint normal made orthogonal to
+// the edge, so the roll around the edge is fixed and the flat side faces outward.
+fn edge_rotation(
+    edge_vec:   Vec3,
+    normal:     Vec3,
+) -> Quat {
+
+    let x_axis = edge_vec.normalize_or_zero();
+    let z_axis = (normal - x_axis * normal.dot(x_axis)).normalize_or_zero();
+
+    // No usable normal (missing, or parallel to the edge): roll is arbitrary
+    if x_axis == Vec3::ZERO || z_axis == Vec3::ZERO {
+        return Quat::from_rotation_arc(Vec3::X, x_axis);
+    }
+
+    let y_axis = z_axis.cross(x_axis);
+
+    Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, z_axis))
+}
+
+
+
+// Pairs coplanar triangles that share their longest edge (the quad diagonal) and emits one
+// rectangular surfel per pair. Triangles without a partner fall back to a triangle surfel.
+fn quad_gaussians(
+    positions:  &[Vec3],
+    tris:       &[[u32; 3]],
+    transform:  Transform,
+    settings:   &MeshConversionSettings,
+    colors:     &SplatColors,
+) -> Vec<Gaussian3d> {
+
+    // Cosine tolerance for treating two triangle normals as coplanar
+    const COPLANAR_COS: f32 = 0.999;
+
+    let longest_edge = |tri: &[u32; 3]| -> (u32, u32) {
+        let edges = [
+            (tri[0], tri[1]),
+            (tri[1], tri[2]),
+            (tri[2], tri[0]),
+        ];
+
+        let (a, b) = edges
+            .into_iter()
+            .max_by(|(a0, b0), (a1, b1)| {
+                let l0 = positions[*a0 as usize].distance_squared(positions[*b0 as usize]);
+                let l1 = positions[*a1 as usize].distance_squared(positions[*b1 as usize]);
+                l0.total_cmp(&l1)
+            })
+            .unwrap();
+
+        if a < b { (a, b) } else { (b, a) }
+    };
+
+    let normal_of = |tri: &[u32; 3]| -> Vec3 {
+        face_normal(
+            positions[tri[0] as usize],
+            positions[tri[1] as usize],
+            positions[tri[2] as usize],
+        )
+    };
+
+    // Diagonal candidates: triangles keyed by their longest edge
+    let mut by_diagonal: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+
+    for (i, tri) in tris.iter().enumerate() {
+        by_diagonal.entry(longest_edge(tri)).or_default().push(i);
+    }
+
+    let mut consumed    = vec![false; tris.len()];
+    let mut out         = Vec::with_capacity(tris.len());
+
+    for (i, tri) in tris.iter().enumerate() {
+
+        if consumed[i] {
+            continue;
+        }
+        consumed[i] = true;
+
+        let diagonal    = longest_edge(tri);
+        let normal      = normal_of(tri);
+
+        let partner = by_diagonal[&diagonal]
+            .iter()
+            .copied()
+            .find(|&j| !consumed[j] && normal_of(&tris[j]).dot(normal) >= COPLANAR_COS);
+
+        let Some(j) = partner else {
+            out.push(triangle_gaussian(positions, *tri, transform, settings, colors));
+            continue;
+        };
+        consumed[j] = true;
+
+        // Quad corners: the shared diagonal plus the opposite vertex of each triangle
+        let opposite = |t: &[u32; 3]| {
+            *t.iter().find(|&&ix| ix != diagonal.0 && ix != diagonal.1).unwrap()
+        };
+
+        let a = positions[diagonal.0 as usize];
+        let b = positions[diagonal.1 as usize];
+        let c = positions[opposite(tri) as usize];
+        let d = positions[opposite(&tris[j]) as usize];
+
+        // Align the surfel with one side of the quad
+        let x_axis = (c - a).normalize_or_zero();
+        let z_axis = normal;
+        let y_axis = z_axis.cross(x_axis);
+
+        let rot = Quat::from_mat3(&Mat3::from_cols(x_axis, y_axis, z_axis));
+
+        let corners = [a, b, c, d];
+        let (mut min_x, mut max_x) = (f32::INFINITY, f32::NEG_INFINITY);
+        let (mut min_y, mut max_y) = (f32::INFINITY, f32::NEG_INFINITY);
+
+        for p in corners {
+            let px = (p - a).dot(x_axis);
+            let py = (p - a).dot(y_axis);
+            min_x = min_x.min(px);
+            max_x = max_x.max(px);
+            min_y = min_y.min(py);
+            max_y = max_y.max(py);
+        }
+
+        let center = a
+            + x_axis * (min_x + max_x) * 0.5
+            + y_axis * (min_y + max_y) * 0.5;
+
+        let scale = clamp_scale(Vec3::new(max_x - min_x, max_y - min_y, settings.face_scale), settings);
+
+        out.push(gaussian_from_transform(
+            transform.transform_point(center),
+            rot,
+            scale,
+            colors.rgb(normal, center),
+            settings.opacity,
+            transform.rotation * normal,
+            settings.sh_degree,
+        ));
+    }
+
+    out
+}
+
+
+
+
+
+// Corner positions of a triangle, or `None` if an index is out of range.
+pub(crate) fn triangle_corners(
+    positions:  &[Vec3],
+    tri:        [u32; 3],
+) -> Option<[Vec3; 3]> {
+
+    Some([
+        *positions.get(tri[0] as usize)?,
+        *positions.get(tri[1] as usize)?,
+        *positions.get(tri[2] as usize)?,
+    ])
+}
+
+
+
+// Whether a triangle has finite corners and enough area to span a surfel basis.
+pub(crate) fn is_valid_triangle(
+    [p0, p1, p2]: [Vec3; 3]
+) -> bool {
+
+    let doubled_area = (p1 - p0).cross(p2 - p0).length();
+
+    doubled_area.is_finite() && doubled_area * 0.5 >= MIN_TRIANGLE_AREA
+}
+
+
+
+// Triangles of an index buffer for the given topology. Strips alternate winding on every
+// odd triangle so all faces keep the orientation of the first; degenerate triangles, which
+// strips use to stitch separate runs together, are skipped.
+pub(crate) fn triangles_from(
     topology:   PrimitiveTopology, 
     indices:    &[u32]
 ) -> impl Iterator<Item = [u32; 3]> + '_ {
@@ -209,6 +642,15 @@ fn triangles_from(
             Box::new(indices.chunks_exact(3).map(|c| [c[0], c[1], c[2]])) 
                 as Box<dyn Iterator<Item = [u32; 3]> + '_>
         },
+        PrimitiveTopology::TriangleStrip => {
+            Box::new(
+                indices
+                    .windows(3)
+                    .enumerate()
+                    .map(|(i, w)| if i % 2 == 0 { [w[0], w[1], w[2]] } else { [w[1], w[0], w[2]] })
+                    .filter(|t| t[0] != t[1] && t[1] != t[2] && t[0] != t[2])
+            )
+        },
         _ => {
             warn!("mesh_to_gaussians: non-triangle topology {:?} not fully supported; attempting naive 3-chunking", topology);
             Box::new(indices.chunks(3).filter(|c| c.len() == 3).map(|c| [c[0], c[1], c[2]]))
@@ -222,31 +664,92 @@ fn triangles_from(
 
 
 
+// Vertices after welding, plus the mapping from original to welded indices.
+#[derive(Default)]
+struct WeldedVertices {
+    positions:  Vec<Vec3>,
+    normals:    Vec<Vec3>,
+    remap:      Vec<u32>,
+}
+
+
+
+// Merges vertices closer than `threshold` into the first vertex of each cluster, averaging
+// their normals. Neighbors are found through a hash grid with `threshold`-sized cells, so only
+// the 27 surrounding cells are searched. A non-positive threshold leaves the vertices as is.
+fn weld_vertices(
+    positions:  &[Vec3],
+    normals:    &[Vec3],
+    threshold:  f32,
+) -> WeldedVertices {
+
+    if threshold <= 0.0 {
+        return WeldedVertices {
+            positions:  positions.to_vec(),
+            normals:    normals.to_vec(),
+            remap:      (0..positions.len() as u32).collect(),
+        };
+    }
+
+    let cell_of = |p: Vec3| (p / threshold).floor().as_ivec3();
+    let threshold_sq = threshold * threshold;
+
+    let mut grid: HashMap<IVec3, Vec<u32>> = HashMap::new();
+    let mut welded = WeldedVertices {
+        remap: Vec::with_capacity(positions.len()),
+        ..default()
+    };
+
+    for (i, &p) in positions.iter().enumerate() {
+
+        let cell    = cell_of(p);
+        let normal  = normals.get(i).copied().unwrap_or(Vec3::ZERO);
+
+        let existing = (-1..=1)
+            .flat_map(|z| (-1..=1).flat_map(move |y| (-1..=1).map(move |x| IVec3::new(x, y, z))))
+            .filter_map(|offset| grid.get(&(cell + offset)))
+            .flatten()
+            .copied()
+            .find(|&w| welded.positions[w as usize].distance_squared(p) <= threshold_sq);
+
+        let index = match existing {
+            Some(w) => {
+                welded.normals[w as usize] += normal;
+                w
+            }
+            None => {
+                let w = welded.positions.len() as u32;
+                welded.positions.push(p);
+                welded.normals.push(normal);
+                grid.entry(cell).or_default().push(w);
+                w
+            }
+        };
+
+        welded.remap.push(index);
+    }
+
+    for n in &mut welded.normals {
+
+        *n = n.normalize_or_zero();
+    }
+
+    welded
+}
+
+
+
+
+
+
+
 // --- Mesh attribute readers ---
 // 
-fn read_positions(
+pub(crate) fn read_positions(
     mesh: &Mesh
 ) -> Option<Vec<Vec3>> {
 
-    let attr = Mesh::ATTRIBUTE_POSITION;
-
-    mesh.attribute(attr).and_then(|a| {
-        match a {
-            VertexAttributeValues::Float32x3(v) => {
-                Some(v.iter().map(|p| Vec3::from_slice(p)).collect())
-            }
-            VertexAttributeValues::Float32x2(v) => {
-                Some(v.iter().map(|p| Vec3::new(p[0], p[1], 0.0)).collect())
-            }
-            VertexAttributeValues::Float32x4(v) => {
-                Some(v.iter().map(|p| Vec3::new(p[0], p[1], p[2])).collect())
-            }
-            VertexAttributeValues::Uint32x3(v) => {
-                Some(v.iter().map(|p| Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32)).collect())
-            }
-            _ => None,
-        }
-    })
+    mesh.attribute(Mesh::ATTRIBUTE_POSITION).and_then(attribute_to_vec3)
 }
 
 
@@ -259,21 +762,75 @@ fn read_normals(
     mesh: &Mesh
 ) -> Option<Vec<Vec3>> {
 
-    let attr = Mesh::ATTRIBUTE_NORMAL;
-    mesh.attribute(attr).and_then(|a| {
-        match a {
-            VertexAttributeValues::Float32x3(v) => {
-                Some(v.iter().map(|p| Vec3::from_slice(p)).collect())
-            }
-            VertexAttributeValues::Float32x4(v) => {
-                Some(v.iter().map(|p| Vec3::new(p[0], p[1], p[2])).collect())
-            }
-            VertexAttributeValues::Uint32x3(v) => {
-                Some(v.iter().map(|p| Vec3::new(p[0] as f32, p[1] as f32, p[2] as f32)).collect())
-            }
-            _ => None,
-        }
-    })
+    // Packed normals lose a little length to quantization
+    mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        .and_then(attribute_to_vec3)
+        .map(|normals| normals.into_iter().map(Vec3::normalize_or_zero).collect())
+}
+
+
+
+
+
+
+
+// Widen any vertex format to Vec3. Two component formats get z = 0 and the fourth component of
+// four component formats is dropped. Normalized formats are scaled to [0, 1] or [-1, 1] like the
+// GPU would read them, plain integer formats are converted as is (glTF dequantizes those through
+// the node transform).
+fn attribute_to_vec3(
+    values: &VertexAttributeValues
+) -> Option<Vec<Vec3>> {
+
+    fn collect<T, const N: usize>(
+        v:      &[[T; N]],
+        f:      impl Fn(T) -> f32,
+    ) -> Vec<Vec3>
+    where
+        T: Copy,
+    {
+        v.iter()
+            .map(|p| Vec3::new(f(p[0]), f(p[1]), if N > 2 { f(p[2]) } else { 0.0 }))
+            .collect()
+    }
+
+    // Both -32768 and -32767 map to -1.0
+    let snorm16 = |x: i16| (x as f32 / i16::MAX as f32).max(-1.0);
+    let snorm8  = |x: i8| (x as f32 / i8::MAX as f32).max(-1.0);
+    let unorm16 = |x: u16| x as f32 / u16::MAX as f32;
+    let unorm8  = |x: u8| x as f32 / u8::MAX as f32;
+
+    use VertexAttributeValues as V;
+    let positions = match values {
+        V::Float32x2(v)                     => collect(v, |x| x),
+        V::Float32x3(v)                     => collect(v, |x| x),
+        V::Float32x4(v)                     => collect(v, |x| x),
+        V::Sint32x2(v)                      => collect(v, |x| x as f32),
+        V::Sint32x3(v)                      => collect(v, |x| x as f32),
+        V::Sint32x4(v)                      => collect(v, |x| x as f32),
+        V::Uint32x2(v)                      => collect(v, |x| x as f32),
+        V::Uint32x3(v)                      => collect(v, |x| x as f32),
+        V::Uint32x4(v)                      => collect(v, |x| x as f32),
+        V::Sint16x2(v)                      => collect(v, f32::from),
+        V::Sint16x4(v)                      => collect(v, f32::from),
+        V::Uint16x2(v)                      => collect(v, f32::from),
+        V::Uint16x4(v)                      => collect(v, f32::from),
+        V::Snorm16x2(v)                     => collect(v, snorm16),
+        V::Snorm16x4(v)                     => collect(v, snorm16),
+        V::Unorm16x2(v)                     => collect(v, unorm16),
+        V::Unorm16x4(v)                     => collect(v, unorm16),
+        V::Sint8x2(v)                       => collect(v, f32::from),
+        V::Sint8x4(v)                       => collect(v, f32::from),
+        V::Uint8x2(v)                       => collect(v, f32::from),
+        V::Uint8x4(v)                       => collect(v, f32::from),
+        V::Snorm8x2(v)                      => collect(v, snorm8),
+        V::Snorm8x4(v)                      => collect(v, snorm8),
+        V::Unorm8x2(v)                      => collect(v, unorm8),
+        V::Unorm8x4(v)                      => collect(v, unorm8),
+        V::Float32(_) | V::Sint32(_) | V::Uint32(_) => return None,
+    };
+
+    Some(positions)
 }
 
 
@@ -325,7 +882,7 @@ fn face_normal(
 
 
 
-fn normal_to_rgb(
+pub(crate) fn normal_to_rgb(
     n: Vec3
 ) -> [f32; 3] {
 
@@ -336,13 +893,95 @@ fn normal_to_rgb(
 
 
 
-// Construct a Gaussian3d from a transform, a normal for color, and an opacity.
-fn gaussian_from_transform(
+// Resolves splat colors for a `ColorMode` over one mesh's local-space bounds.
+struct SplatColors {
+    mode:   ColorMode,
+    min:    Vec3,
+    extent: Vec3,
+}
+
+impl SplatColors {
+
+    fn new(
+        mode:       ColorMode,
+        positions:  &[Vec3]
+    ) -> Self {
+
+        let min = positions.iter().copied().fold(Vec3::splat(f32::INFINITY), Vec3::min);
+        let max = positions.iter().copied().fold(Vec3::splat(f32::NEG_INFINITY), Vec3::max);
+
+        if positions.is_empty() {
+            return Self { mode, min: Vec3::ZERO, extent: Vec3::ZERO };
+        }
+
+        Self { mode, min, extent: max - min }
+    }
+
+    // `position` is in mesh space, before the conversion transform is applied.
+    fn rgb(
+        &self,
+        normal:     Vec3,
+        position:   Vec3,
+    ) -> [f32; 3] {
+
+        match self.mode {
+            ColorMode::Normal       => normal_to_rgb(normal),
+            ColorMode::Solid(rgb)   => rgb,
+
+            // Gradient runs along the longest axis of the mesh bounds
+            ColorMode::Gradient { from, to } => {
+                let e = self.extent;
+                let axis = if e.x >= e.y && e.x >= e.z { 0 } else if e.y >= e.z { 1 } else { 2 };
+                let t = if self.extent[axis] > 0.0 {
+                    ((position[axis] - self.min[axis]) / self.extent[axis]).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+
+                Vec3::from(from).lerp(Vec3::from(to), t).to_array()
+            }
+
+            // Seeded by the splat position so colors don't depend on emission order
+            ColorMode::Random { seed } => {
+                let key = position.x.to_bits()
+                    ^ position.y.to_bits().rotate_left(11)
+                    ^ position.z.to_bits().rotate_left(22);
+
+                hash_to_vec3(seed, key).to_array()
+            }
+
+            // Callers with per-point colors resolve them before falling back here
+            ColorMode::PerPoint => normal_to_rgb(normal),
+        }
+    }
+}
+
+
+
+// Keeps each scale axis inside `settings.scale_clamp`, so sliver triangles and long edges don't
+// turn into invisible or streaking splats.
+fn clamp_scale(
+    scale:      Vec3,
+    settings:   &MeshConversionSettings,
+) -> Vec3 {
+
+    let (min, max) = settings.scale_clamp;
+
+    scale.max(min).min(max)
+}
+
+
+
+// Construct a Gaussian3d from a transform, an RGB color, and an opacity. With an `sh_degree` of
+// 1 or more the first SH band also gets a view gradient facing `normal`; higher bands stay zero.
+pub(crate) fn gaussian_from_transform(
     pos:        Vec3,
     rot:        Quat,
     scale:      Vec3,
-    norm:       Vec3,
+    rgb:        [f32; 3],
     opacity:    f32,
+    normal:     Vec3,
+    sh_degree:  u8,
 ) -> Gaussian3d {
 
     let mut g = Gaussian3d::default();
@@ -352,24 +991,205 @@ fn gaussian_from_transform(
     g.position_visibility.visibility    = 1.0;
 
     // rotation
-    g.rotation.rotation = rot.to_array();
+    g.rotation.rotation = stored_rotation(rot);
 
     // scale and opacity
     g.scale_opacity.scale   = scale.to_array();
     g.scale_opacity.opacity = opacity;
 
     // Color via SH DC coefficients
-    // With sh0 feature: sh = (rgb - 0.5) / 0.2821
-    let rgb = normal_to_rgb(norm);
-    
-    g.spherical_harmonic.set(0, (rgb[0] - 0.5) / 0.2821);
-    g.spherical_harmonic.set(1, (rgb[1] - 0.5) / 0.2821);
-    g.spherical_harmonic.set(2, (rgb[2] - 0.5) / 0.2821);
+    let dc = rgb_to_sh_dc(rgb);
+
+    g.spherical_harmonic.set(0, dc[0]);
+    g.spherical_harmonic.set(1, dc[1]);
+    g.spherical_harmonic.set(2, dc[2]);
     
     // zero the rest for determinism
-    for i in 3..bevy_gaussian_splatting::material::spherical_harmonics::SH_COEFF_COUNT {
+    for i in 3..SH_COEFF_COUNT {
         g.spherical_harmonic.set(i, 0.0);
     }
 
+    // The degree-1 band needs coefficients 3..12
+    if sh_degree >= 1 && SH_COEFF_COUNT >= 12 {
+        for (i, c) in rgb_to_sh_view_gradient(rgb, normal, SH_VIEW_GRADIENT).into_iter().enumerate() {
+            g.spherical_harmonic.set(3 + i, c);
+        }
+    }
+
     g
 }
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::RenderAssetUsages;
+
+    use super::*;
+    use crate::gaussian::mesh_triangle_indices;
+
+    fn mesh(
+        topology:   PrimitiveTopology,
+        positions:  Vec<[f32; 3]>,
+        indices:    Option<Indices>,
+    ) -> Mesh {
+        let mut mesh = Mesh::new(topology, RenderAssetUsages::default());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        if let Some(indices) = indices {
+            mesh.insert_indices(indices);
+        }
+        mesh
+    }
+
+    fn colors(mode: ColorMode) -> Vec<[f32; 3]> {
+        let settings    = PointCloudSettings { color_mode: mode, ..default() };
+        let positions   = [Vec3::ZERO, Vec3::X, Vec3::new(0.5, 2.0, -1.0)];
+
+        points_to_gaussians(&positions, None, Transform::IDENTITY, &settings)
+            .iter()
+            .map(|g| {
+                let sh = g.spherical_harmonic.coefficients;
+                [sh[0], sh[1], sh[2]]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn random_colors_follow_the_seed() {
+        assert_eq!(colors(ColorMode::Random { seed: 7 }), colors(ColorMode::Random { seed: 7 }));
+        assert_ne!(colors(ColorMode::Random { seed: 7 }), colors(ColorMode::Random { seed: 8 }));
+    }
+
+    #[test]
+    fn non_indexed_mesh_drops_the_partial_triangle() {
+        let positions   = (0..7).map(|i| [i as f32, (i % 2) as f32, 0.0]).collect();
+        let mesh        = mesh(PrimitiveTopology::TriangleList, positions, None);
+
+        assert_eq!(mesh_triangle_indices(&mesh), (0..6).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn parallel_edges_share_a_rotation() {
+        let normal  = Vec3::new(0.3, 1.0, 0.0);
+        let a       = edge_rotation(Vec3::new(2.0, 0.0, 0.0), normal);
+        let b       = edge_rotation(Vec3::new(0.5, 0.0, 0.0), normal);
+
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            assert!((a * axis).abs_diff_eq(b * axis, 1e-5));
+        }
+        assert!((a * Vec3::X).abs_diff_eq(Vec3::X, 1e-5));
+        assert!((a * Vec3::Z).abs_diff_eq(Vec3::Y, 1e-5));
+    }
+
+    #[test]
+    fn strips_alternate_winding() {
+        let strip: Vec<[u32; 3]> = triangles_from(PrimitiveTopology::TriangleStrip, &[0, 1, 2, 3, 4]).collect();
+        assert_eq!(strip, vec![[0, 1, 2], [2, 1, 3], [2, 3, 4]]);
+
+        // A zig-zag strip in the XY plane: every face must point the same way
+        let positions: Vec<Vec3> = (0..5).map(|i| Vec3::new(i as f32, (i % 2) as f32, 0.0)).collect();
+        for [a, b, c] in strip {
+            let (a, b, c) = (positions[a as usize], positions[b as usize], positions[c as usize]);
+            assert!((b - a).cross(c - a).normalize().abs_diff_eq(Vec3::NEG_Z, 1e-6));
+        }
+
+        // Degenerate triangles stitching two runs are dropped
+        let stitched: Vec<[u32; 3]> = triangles_from(PrimitiveTopology::TriangleStrip, &[0, 1, 2, 3, 3, 4, 4, 5, 6]).collect();
+        assert_eq!(stitched, vec![[0, 1, 2], [2, 1, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn sliver_triangle_scales_are_clamped() {
+        let sliver = mesh(
+            PrimitiveTopology::TriangleList,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.5, 1e-7, 0.0]],
+            Some(Indices::U32(vec![0, 1, 2])),
+        );
+
+        let unclamped = mesh_to_gaussians(&sliver, Transform::IDENTITY, &MeshConversionSettings::default());
+        assert!(unclamped[0].scale_opacity.scale[1] < 1e-6);
+
+        let settings = MeshConversionSettings { scale_clamp: (Vec3::splat(0.005), Vec3::splat(0.5)), ..default() };
+        let clamped = mesh_to_gaussians(&sliver, Transform::IDENTITY, &settings);
+        assert_eq!(clamped[0].scale_opacity.scale, [0.5, 0.005, settings.face_scale]);
+    }
+
+    #[test]
+    fn degenerate_input_gives_no_nan() {
+        // A good triangle, a zero-area one through a duplicated vertex, one with a NaN vertex
+        // and one collapsed to a point
+        let broken = mesh(
+            PrimitiveTopology::TriangleList,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 0.0], [f32::NAN, 0.0, 0.0]],
+            Some(Indices::U32(vec![0, 1, 2, 0, 3, 1, 0, 1, 4, 0, 0, 0])),
+        );
+
+        let modes = [
+            MeshToGaussianMode::TrianglesOneToOne,
+            MeshToGaussianMode::Quads,
+            MeshToGaussianMode::SubdividedTriangles { levels: 2 },
+            MeshToGaussianMode::ThickShell { layers: 2, offset: 0.1 },
+        ];
+
+        for mode in modes {
+            let settings = MeshConversionSettings {
+                mode,
+                include_vertices:   true,
+                include_edges:      true,
+                with_normals:       true,
+                ..default()
+            };
+
+            let (gaussians, normals) = mesh_to_gaussians_with_normals(&broken, Transform::IDENTITY, &settings);
+            assert_eq!(normals.unwrap().len(), gaussians.len());
+
+            for g in &gaussians {
+                let mut values = g.position_visibility.position
+                    .iter()
+                    .chain(&g.rotation.rotation)
+                    .chain(&g.scale_opacity.scale);
+
+                assert!(values.all(|v| v.is_finite()), "{mode:?}: {g:?}");
+            }
+        }
+
+        // Only the good triangle survives by default
+        assert_eq!(mesh_to_gaussians(&broken, Transform::IDENTITY, &MeshConversionSettings::default()).len(), 1);
+    }
+
+    #[test]
+    fn packed_positions_are_widened() {
+        use bevy::render::{mesh::MeshVertexAttribute, render_resource::VertexFormat};
+
+        let sint16 = VertexAttributeValues::Sint16x2(vec![[0, 0], [100, 0], [0, -200]]);
+        assert_eq!(
+            attribute_to_vec3(&sint16).unwrap(),
+            vec![Vec3::ZERO, Vec3::new(100.0, 0.0, 0.0), Vec3::new(0.0, -200.0, 0.0)],
+        );
+
+        let snorm16 = VertexAttributeValues::Snorm16x4(vec![[0, 0, 0, 0], [32767, 0, 0, 0], [0, -32768, 0, 0]]);
+        assert_eq!(
+            attribute_to_vec3(&snorm16).unwrap(),
+            vec![Vec3::ZERO, Vec3::X, Vec3::NEG_Y],
+        );
+
+        // Through a whole conversion, the splat sits at the centroid of the widened triangle
+        for (format, values, centroid) in [
+            (VertexFormat::Sint16x2, sint16, Vec3::new(100.0, -200.0, 0.0) / 3.0),
+            (VertexFormat::Snorm16x4, snorm16, Vec3::new(1.0, -1.0, 0.0) / 3.0),
+        ] {
+            let mut packed = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+            packed.insert_attribute(MeshVertexAttribute::new("Vertex_Position", 0, format), values);
+            packed.insert_indices(Indices::U32(vec![0, 1, 2]));
+
+            let gaussians = mesh_to_gaussians(&packed, Transform::IDENTITY, &MeshConversionSettings::default());
+            assert_eq!(gaussians.len(), 1);
+            assert!(Vec3::from(gaussians[0].position_visibility.position).abs_diff_eq(centroid, 1e-4));
+        }
+    }
+}
+
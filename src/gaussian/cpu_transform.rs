@@ -0,0 +1,1041 @@
+// CPU-side cloud transformations. Pure functions over Gaussian3d slices that return new clouds,
+// plus in-place iteration over planar clouds too large to copy.
+
+use std::collections::HashMap;
+
+use bevy::{
+    prelude::*,
+    render::primitives::{Frustum, Sphere},
+    tasks::{ComputeTaskPool, TaskPool},
+};
+use bevy_gaussian_splatting::{
+    gaussian::f32::{PositionVisibility, Rotation, ScaleOpacity},
+    Gaussian3d,
+    PlanarGaussian3d,
+    SphericalHarmonicCoefficients,
+};
+
+use crate::gaussian::color::{rgb_to_sh_dc, sh_dc_to_rgb};
+use crate::gaussian::noise::noise_vec3;
+use crate::gaussian::rng::{hash_combine, hash_to_unit, hash_to_vec3};
+use crate::sdf_module::{compute_sdf_normal, SDF};
+
+
+
+
+
+
+
+/// Transpose an array of gaussians into the struct-of-arrays layout used by the renderer.
+pub fn to_planar(
+    gaussians: &[Gaussian3d]
+) -> PlanarGaussian3d {
+
+    PlanarGaussian3d {
+        position_visibility:    gaussians.iter().map(|g| g.position_visibility).collect(),
+        spherical_harmonic:     gaussians.iter().map(|g| g.spherical_harmonic).collect(),
+        rotation:               gaussians.iter().map(|g| g.rotation).collect(),
+        scale_opacity:          gaussians.iter().map(|g| g.scale_opacity).collect(),
+    }
+}
+
+
+
+/// Inverse of [`to_planar`]: gather a planar cloud back into one `Gaussian3d` per splat.
+pub fn from_planar(
+    cloud: &PlanarGaussian3d
+) -> Vec<Gaussian3d> {
+
+    cloud.position_visibility
+        .iter()
+        .zip(&cloud.spherical_harmonic)
+        .zip(&cloud.rotation)
+        .zip(&cloud.scale_opacity)
+        .map(|(((pv, sh), rot), so)| Gaussian3d {
+            position_visibility:    *pv,
+            spherical_harmonic:     *sh,
+            rotation:               *rot,
+            scale_opacity:          *so,
+        })
+        .collect()
+}
+
+
+
+
+
+
+
+/// Visit every splat of a planar cloud in place, in order, without gathering the cloud into a
+/// `Vec<Gaussian3d>`. The callback gets the splat's index and a copy to modify, which is written
+/// back to the four arrays afterwards.
+///
+/// Per-gaussian helpers plug straight in:
+/// `for_each_splat_mut(&mut cloud, |i, g| *g = noise_displace(g, i, t, 0.1, 2.0))`.
+pub fn for_each_splat_mut<F>(
+    cloud:  &mut PlanarGaussian3d,
+    mut f:  F,
+)
+where
+    F: FnMut(usize, &mut Gaussian3d),
+{
+
+    let PlanarGaussian3d { position_visibility, spherical_harmonic, rotation, scale_opacity } = cloud;
+
+    update_splats(0, position_visibility, spherical_harmonic, rotation, scale_opacity, &mut f);
+}
+
+
+
+/// Parallel [`for_each_splat_mut`]: the cloud is split into contiguous chunks that are updated
+/// on the compute task pool. Visiting order across chunks is unspecified, so the callback
+/// should only depend on the splat and its index.
+pub fn par_for_each_splat_mut<F>(
+    cloud:  &mut PlanarGaussian3d,
+    f:      F,
+)
+where
+    F: Fn(usize, &mut Gaussian3d) + Sync,
+{
+
+    let PlanarGaussian3d { position_visibility, spherical_harmonic, rotation, scale_opacity } = cloud;
+
+    let len = position_visibility.len()
+        .min(spherical_harmonic.len())
+        .min(rotation.len())
+        .min(scale_opacity.len());
+    if len == 0 {
+        return;
+    }
+
+    let pool        = ComputeTaskPool::get_or_init(TaskPool::default);
+    let chunk_size  = len.div_ceil(pool.thread_num().max(1) * 4).max(1);
+    let f           = &f;
+
+    pool.scope(|scope| {
+        let chunks = position_visibility[..len].chunks_mut(chunk_size)
+            .zip(spherical_harmonic[..len].chunks_mut(chunk_size))
+            .zip(rotation[..len].chunks_mut(chunk_size))
+            .zip(scale_opacity[..len].chunks_mut(chunk_size))
+            .enumerate();
+
+        for (chunk, (((pv, sh), rot), so)) in chunks {
+            scope.spawn(async move {
+                update_splats(chunk * chunk_size, pv, sh, rot, so, &mut |i, g| f(i, g));
+            });
+        }
+    });
+}
+
+
+
+// Gather, update and scatter back one splat at a time. Stops at the shortest array.
+fn update_splats(
+    first_index:            usize,
+    position_visibility:    &mut [PositionVisibility],
+    spherical_harmonic:     &mut [SphericalHarmonicCoefficients],
+    rotation:               &mut [Rotation],
+    scale_opacity:          &mut [ScaleOpacity],
+    f:                      &mut dyn FnMut(usize, &mut Gaussian3d),
+) {
+
+    let splats = position_visibility
+        .iter_mut()
+        .zip(spherical_harmonic)
+        .zip(rotation)
+        .zip(scale_opacity)
+        .enumerate();
+
+    for (i, (((pv, sh), rot), so)) in splats {
+        let mut g = Gaussian3d {
+            position_visibility:    *pv,
+            spherical_harmonic:     *sh,
+            rotation:               *rot,
+            scale_opacity:          *so,
+        };
+
+        f(first_index + i, &mut g);
+
+        *pv     = g.position_visibility;
+        *sh     = g.spherical_harmonic;
+        *rot    = g.rotation;
+        *so     = g.scale_opacity;
+    }
+}
+
+
+
+
+
+
+
+/// Move a cloud into the space of `transform`: positions are transformed, rotations composed
+/// and scales multiplied per axis.
+///
+/// Scale is applied in each splat's local frame, so non-uniform transform scales are only
+/// exact for splats aligned with the transform's axes.
+pub fn transform_cloud(
+    gaussians:  &[Gaussian3d],
+    transform:  Transform,
+) -> Vec<Gaussian3d> {
+
+    gaussians
+        .iter()
+        .map(|g| {
+            let mut out = *g;
+            transform_gaussian(&mut out, transform);
+            out
+        })
+        .collect()
+}
+
+
+
+/// [`transform_cloud`] applied to a planar cloud in place, for clouds too large to copy.
+pub fn transform_cloud_in_place(
+    cloud:      &mut PlanarGaussian3d,
+    transform:  Transform,
+) {
+
+    par_for_each_splat_mut(cloud, |_, g| transform_gaussian(g, transform));
+}
+
+
+
+fn transform_gaussian(
+    g:          &mut Gaussian3d,
+    transform:  Transform,
+) {
+
+    let position    = transform.transform_point(Vec3::from(g.position_visibility.position));
+    let rotation    = transform.rotation * unit_rotation(g);
+    let scale       = Vec3::from(g.scale_opacity.scale) * transform.scale.abs();
+
+    g.position_visibility.position  = position.to_array();
+    g.rotation.rotation             = stored_rotation(rotation.normalize());
+    g.scale_opacity.scale           = scale.to_array();
+}
+
+
+
+/// Concatenate clouds in order into one.
+pub fn combine_clouds(
+    clouds: &[&[Gaussian3d]]
+) -> Vec<Gaussian3d> {
+
+    clouds.concat()
+}
+
+
+
+/// Concatenate independently placed clouds into one, moving each into world space with its
+/// transform first (see [`transform_cloud`]). Useful for sorting several clouds as one.
+pub fn combine_clouds_with_transforms(
+    clouds: &[(&[Gaussian3d], Transform)]
+) -> Vec<Gaussian3d> {
+
+    clouds
+        .iter()
+        .flat_map(|(gaussians, transform)| transform_cloud(gaussians, *transform))
+        .collect()
+}
+
+
+
+
+
+
+
+/// Set each gaussian's visibility to 1.0 or 0.0 based on a predicate, without removing any.
+///
+/// The output has the same length and order as the input, so per-index correspondence
+/// between clouds (as needed for interpolation) is preserved.
+pub fn set_visibility_where<F>(
+    gaussians:  &[Gaussian3d],
+    predicate:  F,
+) -> Vec<Gaussian3d>
+where
+    F: Fn(&Gaussian3d) -> bool,
+{
+
+    gaussians
+        .iter()
+        .map(|g| {
+            let mut out = *g;
+            out.position_visibility.visibility = if predicate(g) { 1.0 } else { 0.0 };
+            out
+        })
+        .collect()
+}
+
+
+
+/// Hide gaussians whose position lies outside the axis-aligned box `[min, max]` (inclusive).
+pub fn cull_by_bounds(
+    gaussians:  &[Gaussian3d],
+    min:        Vec3,
+    max:        Vec3,
+) -> Vec<Gaussian3d> {
+
+    set_visibility_where(gaussians, |g| {
+        let p = Vec3::from(g.position_visibility.position);
+        p.cmpge(min).all() && p.cmple(max).all()
+    })
+}
+
+
+
+/// Hide gaussians whose center lies outside the view frustum of `camera` at `camera_transform`.
+///
+/// Each center is tested as a sphere of radius `margin`, so a positive margin keeps splats that
+/// poke into view from just outside. The camera's projection must already be computed, i.e.
+/// this should run after Bevy's camera systems have seen it once.
+pub fn cull_cloud_to_frustum(
+    gaussians:          &[Gaussian3d],
+    camera:             &Camera,
+    camera_transform:   &GlobalTransform,
+    margin:             f32,
+) -> Vec<Gaussian3d> {
+
+    let clip_from_world = camera.clip_from_view() * camera_transform.compute_matrix().inverse();
+    let frustum         = Frustum::from_clip_from_world(&clip_from_world);
+
+    set_visibility_where(gaussians, |g| {
+        let sphere = Sphere {
+            center: Vec3A::from(g.position_visibility.position),
+            radius: margin,
+        };
+        frustum.intersects_sphere(&sphere, true)
+    })
+}
+
+
+
+/// Fade opacity with distance from `camera_pos`: unchanged up to `near`, falling linearly to
+/// zero at `far`. Gaussians beyond `far` are also hidden (`visibility = 0.0`).
+///
+/// If `far <= near` the fade collapses to a hard cutoff at `far`.
+pub fn fade_cloud_by_distance(
+    gaussians:  &[Gaussian3d],
+    camera_pos: Vec3,
+    near:       f32,
+    far:        f32,
+) -> Vec<Gaussian3d> {
+
+    let range = far - near;
+
+    gaussians
+        .iter()
+        .map(|g| {
+            let mut out = *g;
+            let distance = Vec3::from(g.position_visibility.position).distance(camera_pos);
+
+            if distance > far {
+                out.scale_opacity.opacity = 0.0;
+                out.position_visibility.visibility = 0.0;
+            } else if distance > near && range > 0.0 {
+                out.scale_opacity.opacity *= 1.0 - (distance - near) / range;
+            }
+            out
+        })
+        .collect()
+}
+
+
+
+
+
+
+
+/// Keep the `target_count` gaussians that contribute most to the image, in their original order.
+///
+/// Splats are scored by footprint (the product of their two largest scale axes) times opacity.
+/// With `compensate_coverage`, survivors are scaled up uniformly so their summed footprint
+/// matches the whole cloud's, which keeps sparse LODs from looking thin. A `target_count` at
+/// or above the cloud's length returns it unchanged.
+pub fn decimate_cloud(
+    gaussians:              &[Gaussian3d],
+    target_count:           usize,
+    compensate_coverage:    bool,
+) -> Vec<Gaussian3d> {
+
+    let kept = decimated_indices(gaussians, target_count);
+    let mut out: Vec<Gaussian3d> = kept.iter().map(|&i| gaussians[i]).collect();
+
+    if compensate_coverage && out.len() < gaussians.len() {
+        compensate_footprint(gaussians, &mut out);
+    }
+
+    out
+}
+
+
+
+/// [`decimate_cloud`] that also keeps the stable ids of the surviving splats, see [`stable_ids`].
+pub fn decimate_cloud_with_ids(
+    gaussians:              &[Gaussian3d],
+    ids:                    &[u32],
+    target_count:           usize,
+    compensate_coverage:    bool,
+) -> (Vec<Gaussian3d>, Vec<u32>) {
+
+    debug_assert_eq!(ids.len(), gaussians.len(), "one id per splat");
+
+    let kept = decimated_indices(gaussians, target_count);
+
+    let (mut out, kept_ids): (Vec<Gaussian3d>, Vec<u32>) = kept
+        .iter()
+        .map(|&i| (gaussians[i], ids[i]))
+        .unzip();
+
+    if compensate_coverage && out.len() < gaussians.len() {
+        compensate_footprint(gaussians, &mut out);
+    }
+
+    (out, kept_ids)
+}
+
+
+
+fn splat_footprint(
+    g: &Gaussian3d
+) -> f32 {
+
+    let mut s = g.scale_opacity.scale.map(f32::abs);
+    s.sort_by(f32::total_cmp);
+    s[1] * s[2]
+}
+
+
+
+// Indices of the `target_count` highest scoring splats, ascending.
+fn decimated_indices(
+    gaussians:      &[Gaussian3d],
+    target_count:   usize,
+) -> Vec<usize> {
+
+    if target_count >= gaussians.len() {
+        return (0..gaussians.len()).collect();
+    }
+
+    let score = |g: &Gaussian3d| splat_footprint(g) * g.scale_opacity.opacity.max(0.0);
+
+    let mut order: Vec<usize> = (0..gaussians.len()).collect();
+    order.sort_by(|&a, &b| score(&gaussians[b]).total_cmp(&score(&gaussians[a])));
+
+    let mut kept = order[..target_count].to_vec();
+    kept.sort_unstable();
+    kept
+}
+
+
+
+// Scale `survivors` up uniformly so their summed footprint matches the whole cloud's.
+fn compensate_footprint(
+    gaussians:  &[Gaussian3d],
+    survivors:  &mut [Gaussian3d],
+) {
+
+    let total       = gaussians.iter().map(splat_footprint).sum::<f32>();
+    let remaining   = survivors.iter().map(splat_footprint).sum::<f32>();
+
+    // Footprint grows with the square of the scale
+    if remaining > 0.0 {
+        let factor = (total / remaining).sqrt();
+        for g in survivors {
+            g.scale_opacity.scale = (Vec3::from(g.scale_opacity.scale) * factor).to_array();
+        }
+    }
+}
+
+
+
+/// Merge all gaussians whose centers fall in the same `cell_size` grid cell into one.
+///
+/// Position, scale, opacity and spherical harmonics are averaged with each splat's opacity as
+/// its weight, and the merged splat takes the rotation of the most opaque one. Cells of fully
+/// transparent splats fall back to a plain average. Cells are emitted in the order their first
+/// gaussian appears. A non-positive `cell_size` returns the input unchanged.
+pub fn merge_cloud(
+    gaussians:  &[Gaussian3d],
+    cell_size:  f32,
+) -> Vec<Gaussian3d> {
+
+    merge_cells(gaussians, cell_size).0
+}
+
+
+
+/// [`merge_cloud`] that also carries stable ids, see [`stable_ids`]. Each merged splat keeps the
+/// id of the first gaussian in its cell.
+pub fn merge_cloud_with_ids(
+    gaussians:  &[Gaussian3d],
+    ids:        &[u32],
+    cell_size:  f32,
+) -> (Vec<Gaussian3d>, Vec<u32>) {
+
+    debug_assert_eq!(ids.len(), gaussians.len(), "one id per splat");
+
+    let (merged, firsts) = merge_cells(gaussians, cell_size);
+    (merged, firsts.iter().map(|&i| ids[i]).collect())
+}
+
+
+
+// The merged cloud and, for every merged splat, the index of the first gaussian in its cell.
+fn merge_cells(
+    gaussians:  &[Gaussian3d],
+    cell_size:  f32,
+) -> (Vec<Gaussian3d>, Vec<usize>) {
+
+    if cell_size <= 0.0 {
+        return (gaussians.to_vec(), (0..gaussians.len()).collect());
+    }
+
+    struct Cell {
+        first:      usize,
+        weight:     f32,
+        position:   Vec3,
+        scale:      Vec3,
+        opacity:    f32,
+        sh:         [f32; 48],
+        visibility: f32,
+        strongest:  (f32, [f32; 4]),
+    }
+
+    let mut slots: HashMap<IVec3, usize>    = HashMap::new();
+    let mut cells: Vec<Cell>                = Vec::new();
+
+    for (i, g) in gaussians.iter().enumerate() {
+        let position    = Vec3::from(g.position_visibility.position);
+        let opacity     = g.scale_opacity.opacity;
+        // Never exactly zero, so an all-transparent cell still averages
+        let weight      = opacity.max(f32::EPSILON);
+
+        let slot = *slots
+            .entry((position / cell_size).floor().as_ivec3())
+            .or_insert_with(|| {
+                cells.push(Cell {
+                    first:      i,
+                    weight:     0.0,
+                    position:   Vec3::ZERO,
+                    scale:      Vec3::ZERO,
+                    opacity:    0.0,
+                    sh:         [0.0; 48],
+                    visibility: 0.0,
+                    strongest:  (f32::NEG_INFINITY, g.rotation.rotation),
+                });
+                cells.len() - 1
+            });
+
+        let cell = &mut cells[slot];
+
+        cell.weight    += weight;
+        cell.position  += position * weight;
+        cell.scale     += Vec3::from(g.scale_opacity.scale) * weight;
+        cell.opacity   += opacity * weight;
+        cell.visibility = cell.visibility.max(g.position_visibility.visibility);
+
+        for (acc, c) in cell.sh.iter_mut().zip(&g.spherical_harmonic.coefficients) {
+            *acc += c * weight;
+        }
+
+        if opacity > cell.strongest.0 {
+            cell.strongest = (opacity, g.rotation.rotation);
+        }
+    }
+
+    let merged = cells
+        .iter()
+        .map(|cell| {
+            let mut out = Gaussian3d::default();
+
+            out.position_visibility.position    = (cell.position / cell.weight).to_array();
+            out.position_visibility.visibility  = cell.visibility;
+            out.scale_opacity.scale             = (cell.scale / cell.weight).to_array();
+            out.scale_opacity.opacity           = cell.opacity / cell.weight;
+            out.rotation.rotation               = cell.strongest.1;
+            out.spherical_harmonic.coefficients = cell.sh.map(|c| c / cell.weight);
+
+            out
+        })
+        .collect();
+
+    (merged, cells.iter().map(|cell| cell.first).collect())
+}
+
+
+
+
+
+
+
+/// Apply a per-gaussian animation function at the given time.
+///
+/// The function receives the gaussian, its index in the cloud and the time, and returns the
+/// animated gaussian. The index changes whenever the cloud is reordered; use
+/// [`animate_cloud_by_id`] when per-splat phases must stay put.
+pub fn animate_cloud<F>(
+    gaussians:  &[Gaussian3d],
+    time:       f32,
+    animation:  F,
+) -> Vec<Gaussian3d>
+where
+    F: Fn(&Gaussian3d, usize, f32) -> Gaussian3d,
+{
+
+    gaussians
+        .iter()
+        .enumerate()
+        .map(|(i, g)| animation(g, i, time))
+        .collect()
+}
+
+
+
+/// Stable per-splat ids for a freshly built cloud: each splat's current index.
+///
+/// Keep the ids next to the cloud and pass them through the `_with_ids` variants of anything
+/// that reorders or drops splats ([`sort_cloud_with_ids`], [`decimate_cloud_with_ids`],
+/// [`merge_cloud_with_ids`]), then animate with [`animate_cloud_by_id`] so per-splat phases
+/// survive. Culling, transforms and animation keep the cloud's length and order, so the ids
+/// stay valid through them; ids of combined clouds are the clouds' ids concatenated.
+pub fn stable_ids(
+    count: usize
+) -> Vec<u32> {
+
+    (0..count as u32).collect()
+}
+
+
+
+/// Same as [`animate_cloud`], but the callback receives each splat's stable id from `ids`
+/// instead of its position in the array, so time-based per-splat effects don't change when the
+/// cloud is reordered. `ids` must hold one id per splat.
+pub fn animate_cloud_by_id<F>(
+    gaussians:  &[Gaussian3d],
+    ids:        &[u32],
+    time:       f32,
+    animation:  F,
+) -> Vec<Gaussian3d>
+where
+    F: Fn(&Gaussian3d, usize, f32) -> Gaussian3d,
+{
+
+    debug_assert_eq!(ids.len(), gaussians.len(), "one id per splat");
+
+    gaussians
+        .iter()
+        .zip(ids)
+        .map(|(g, &id)| animation(g, id as usize, time))
+        .collect()
+}
+
+
+
+/// Sort a cloud by `key`, ascending, carrying its stable ids along. `ids` must hold one id per
+/// splat.
+pub fn sort_cloud_with_ids<F>(
+    gaussians:  &[Gaussian3d],
+    ids:        &[u32],
+    key:        F,
+) -> (Vec<Gaussian3d>, Vec<u32>)
+where
+    F: Fn(&Gaussian3d) -> f32,
+{
+
+    debug_assert_eq!(ids.len(), gaussians.len(), "one id per splat");
+
+    let mut order: Vec<usize> = (0..gaussians.len()).collect();
+    order.sort_by(|&a, &b| key(&gaussians[a]).total_cmp(&key(&gaussians[b])));
+
+    order
+        .into_iter()
+        .map(|i| (gaussians[i], ids[i]))
+        .unzip()
+}
+
+
+
+/// Offset a gaussian by coherent 3D value noise sampled at `position * frequency + time`.
+///
+/// Matches the [`animate_cloud`] callback shape once `amplitude` and `frequency` are bound:
+/// `animate_cloud(&cloud, t, |g, i, t| noise_displace(g, i, t, 0.1, 2.0))`.
+pub fn noise_displace(
+    gaussian:   &Gaussian3d,
+    _index:     usize,
+    time:       f32,
+    amplitude:  f32,
+    frequency:  f32,
+) -> Gaussian3d {
+
+    let mut out = *gaussian;
+
+    let pos     = Vec3::from(gaussian.position_visibility.position);
+    let offset  = noise_vec3(pos * frequency + Vec3::splat(time)) * amplitude;
+
+    out.position_visibility.position = (pos + offset).to_array();
+    out
+}
+
+
+
+/// Break up the regularity of generated clouds with a seeded per-splat random offset of up to
+/// `pos_amount` along each axis, scale factor of `exp(±scale_amount)` and opacity change of up
+/// to `±opacity_amount`.
+///
+/// The same seed always gives the same result. Scales stay positive and opacities are clamped
+/// to `[0, 1]`; rotations and colors are kept.
+pub fn jitter_cloud(
+    gaussians:      &[Gaussian3d],
+    seed:           u32,
+    pos_amount:     f32,
+    scale_amount:   f32,
+    opacity_amount: f32,
+) -> Vec<Gaussian3d> {
+
+    // Separate streams, so changing one amount doesn't reshuffle the others
+    let scale_seed      = hash_combine(seed, 1);
+    let opacity_seed    = hash_combine(seed, 2);
+
+    gaussians
+        .iter()
+        .enumerate()
+        .map(|(i, g)| {
+            let mut out = *g;
+            let i       = i as u32;
+
+            let offset  = (hash_to_vec3(seed, i) * 2.0 - 1.0) * pos_amount;
+            let factor  = ((hash_to_unit(scale_seed, i) * 2.0 - 1.0) * scale_amount).exp();
+            let delta   = (hash_to_unit(opacity_seed, i) * 2.0 - 1.0) * opacity_amount;
+
+            let position = Vec3::from(g.position_visibility.position) + offset;
+
+            out.position_visibility.position    = position.to_array();
+            out.scale_opacity.scale             = g.scale_opacity.scale.map(|s| s * factor);
+            out.scale_opacity.opacity           = (g.scale_opacity.opacity + delta).clamp(0.0, 1.0);
+            out
+        })
+        .collect()
+}
+
+
+
+/// Move every gaussian up to `step` along the SDF gradient towards the zero level set, never
+/// past it. Calling this every frame grows any cloud onto the surface of `sdf`.
+///
+/// Splats where the gradient vanishes (e.g. the center of a sphere) stay put.
+pub fn attract_to_sdf(
+    gaussians:  &[Gaussian3d],
+    sdf:        &dyn SDF,
+    step:       f32,
+) -> Vec<Gaussian3d> {
+
+    const GRADIENT_EPSILON: f32 = 1e-3;
+
+    gaussians
+        .iter()
+        .map(|g| {
+            let mut out = *g;
+
+            let pos         = Vec3::from(g.position_visibility.position);
+            let distance    = sdf.distance(pos);
+            let normal      = compute_sdf_normal(sdf, pos, GRADIENT_EPSILON);
+            let travel      = distance.abs().min(step.max(0.0)) * distance.signum();
+
+            out.position_visibility.position = (pos - normal * travel).to_array();
+            out
+        })
+        .collect()
+}
+
+
+
+/// Stretch opacities so the 5th to 95th percentile spans `[0, 1]`, for imported clouds that
+/// render too faint or too washed out. See [`stretch_opacity_percentiles`].
+pub fn auto_normalize_opacity(
+    gaussians: &[Gaussian3d]
+) -> Vec<Gaussian3d> {
+
+    stretch_opacity_percentiles(gaussians, 0.05, 0.95)
+}
+
+
+
+/// Linearly remap opacities so the `low` quantile maps to 0 and the `high` quantile to 1,
+/// clamping the tails. Quantiles are taken over visible splats and given in `[0, 1]`.
+///
+/// The cloud is returned unchanged when it has no visible splats or the two quantiles coincide.
+pub fn stretch_opacity_percentiles(
+    gaussians:  &[Gaussian3d],
+    low:        f32,
+    high:       f32,
+) -> Vec<Gaussian3d> {
+
+    let mut opacities: Vec<f32> = gaussians
+        .iter()
+        .filter(|g| g.position_visibility.visibility > 0.0)
+        .map(|g| g.scale_opacity.opacity)
+        .collect();
+
+    if opacities.is_empty() {
+        return gaussians.to_vec();
+    }
+
+    opacities.sort_by(f32::total_cmp);
+
+    let quantile = |q: f32| {
+        let index = (q.clamp(0.0, 1.0) * (opacities.len() - 1) as f32).round() as usize;
+        opacities[index]
+    };
+
+    let (lo, hi) = (quantile(low), quantile(high));
+    if hi - lo <= f32::EPSILON {
+        return gaussians.to_vec();
+    }
+
+    gaussians
+        .iter()
+        .map(|g| {
+            let mut out = *g;
+            out.scale_opacity.opacity = ((g.scale_opacity.opacity - lo) / (hi - lo)).clamp(0.0, 1.0);
+            out
+        })
+        .collect()
+}
+
+
+
+/// Rotate every gaussian's base color around the hue wheel by `time * hue_speed` turns.
+///
+/// Only the view-independent DC term is changed; saturation, value and the higher SH bands
+/// are kept, so grey splats stay grey.
+pub fn animate_cloud_color(
+    gaussians:  &[Gaussian3d],
+    time:       f32,
+    hue_speed:  f32,
+) -> Vec<Gaussian3d> {
+
+    let degrees = (time * hue_speed).fract() * 360.0;
+
+    gaussians
+        .iter()
+        .map(|g| {
+            let mut out = *g;
+            let sh      = &mut out.spherical_harmonic.coefficients;
+
+            let [red, green, blue]  = sh_dc_to_rgb([sh[0], sh[1], sh[2]]);
+            let rotated             = Srgba::from(Hsva::from(Srgba::rgb(red, green, blue)).rotate_hue(degrees));
+
+            sh[..3].copy_from_slice(&rgb_to_sh_dc([rotated.red, rotated.green, rotated.blue]));
+            out
+        })
+        .collect()
+}
+
+
+
+
+
+
+
+/// Cross-dissolve between two unrelated clouds: both are kept, `from` with its opacities scaled
+/// by `1 - t` and `to` with its opacities scaled by `t`, `t` clamped to `[0, 1]`.
+///
+/// Unlike [`interpolate_clouds`] nothing moves and the clouds needn't correspond. The output is
+/// always `from` followed by `to`, so its length doesn't change over the fade and a cloud asset
+/// updated every frame keeps its size.
+pub fn crossfade_clouds(
+    from:   &[Gaussian3d],
+    to:     &[Gaussian3d],
+    t:      f32,
+) -> Vec<Gaussian3d> {
+
+    let t = t.clamp(0.0, 1.0);
+
+    let fade = |g: &Gaussian3d, weight: f32| {
+        let mut out = *g;
+        out.scale_opacity.opacity *= weight;
+        out
+    };
+
+    from.iter()
+        .map(|g| fade(g, 1.0 - t))
+        .chain(to.iter().map(|g| fade(g, t)))
+        .collect()
+}
+
+
+
+/// Interpolate between two clouds gaussian by gaussian, pairing splats by index.
+///
+/// Positions, scales, opacities and visibilities are lerped and rotations slerped. All
+/// spherical harmonic coefficients are lerped as well, which isn't physically accurate for
+/// view-dependent clouds; see `interpolate_clouds_sh_rotated` (feature
+/// `sh_rotated_interpolation`) for the slower, rotation-aware alternative.
+///
+/// If the clouds differ in length the extra gaussians of the longer one are dropped.
+pub fn interpolate_clouds(
+    from:   &[Gaussian3d],
+    to:     &[Gaussian3d],
+    t:      f32,
+) -> Vec<Gaussian3d> {
+
+    from.iter()
+        .zip(to)
+        .map(|(a, b)| {
+            let mut out = lerp_gaussian(a, b, t);
+            for (o, (x, y)) in out.spherical_harmonic.coefficients
+                .iter_mut()
+                .zip(a.spherical_harmonic.coefficients.iter().zip(&b.spherical_harmonic.coefficients))
+            {
+                *o = x.lerp(*y, t);
+            }
+            out
+        })
+        .collect()
+}
+
+
+
+/// Like [`interpolate_clouds`], but the view-dependent SH bands follow each splat's rotation.
+///
+/// Both endpoints' higher-order bands are rotated into the interpolated orientation before
+/// they are blended, so lobes turn with the splat instead of fading through each other. The
+/// DC term is rotation invariant and is lerped directly. See `sh_rotation` for the assumed
+/// band layout (degree 3, 16 coefficients × 3 channels, interleaved).
+#[cfg(feature = "sh_rotated_interpolation")]
+pub fn interpolate_clouds_sh_rotated(
+    from:   &[Gaussian3d],
+    to:     &[Gaussian3d],
+    t:      f32,
+) -> Vec<Gaussian3d> {
+
+    let fit = crate::gaussian::sh_rotation::ShRotationFit::default();
+
+    from.iter()
+        .zip(to)
+        .map(|(a, b)| {
+            let mut out = lerp_gaussian(a, b, t);
+
+            let rotation    = unit_rotation(&out);
+            let sh_a        = fit.rotate(&a.spherical_harmonic, rotation * unit_rotation(a).inverse());
+            let sh_b        = fit.rotate(&b.spherical_harmonic, rotation * unit_rotation(b).inverse());
+
+            for (o, (x, y)) in out.spherical_harmonic.coefficients
+                .iter_mut()
+                .zip(sh_a.coefficients.iter().zip(&sh_b.coefficients))
+            {
+                *o = x.lerp(*y, t);
+            }
+            out
+        })
+        .collect()
+}
+
+
+
+/// Interpolate everything except the spherical harmonics, which are copied from `a`.
+fn lerp_gaussian(
+    a: &Gaussian3d,
+    b: &Gaussian3d,
+    t: f32,
+) -> Gaussian3d {
+
+    let mut out = *a;
+
+    let pos_a   = Vec3::from(a.position_visibility.position);
+    let pos_b   = Vec3::from(b.position_visibility.position);
+    let scale_a = Vec3::from(a.scale_opacity.scale);
+    let scale_b = Vec3::from(b.scale_opacity.scale);
+
+    out.position_visibility.position    = pos_a.lerp(pos_b, t).to_array();
+    out.position_visibility.visibility  = a.position_visibility.visibility.lerp(b.position_visibility.visibility, t);
+    out.scale_opacity.scale             = scale_a.lerp(scale_b, t).to_array();
+    out.scale_opacity.opacity           = a.scale_opacity.opacity.lerp(b.scale_opacity.opacity, t);
+    out.rotation.rotation               = stored_rotation(unit_rotation(a).slerp(unit_rotation(b), t));
+
+    out
+}
+
+
+
+/// A gaussian's rotation as a unit quaternion, falling back to identity for degenerate data.
+pub(crate) fn unit_rotation(g: &Gaussian3d) -> Quat {
+    let [w, x, y, z] = g.rotation.rotation;
+    let q = Quat::from_xyzw(x, y, z, w);
+    if q.length_squared() > f32::EPSILON { q.normalize() } else { Quat::IDENTITY }
+}
+
+/// `q` in the scalar-first `[w, x, y, z]` order clouds store rotations in, like the renderer
+/// and the compute shaders.
+pub(crate) fn stored_rotation(q: Quat) -> [f32; 4] {
+    [q.w, q.x, q.y, q.z]
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line_cloud(count: usize) -> Vec<Gaussian3d> {
+        (0..count)
+            .map(|i| {
+                let mut g = Gaussian3d::default();
+                g.position_visibility.position  = [i as f32, 0.0, 0.0];
+                g.scale_opacity.scale           = [0.1, 0.1, 0.1];
+                g.scale_opacity.opacity         = i as f32 / count as f32;
+                g
+            })
+            .collect()
+    }
+
+    fn positions(gaussians: &[Gaussian3d]) -> Vec<[f32; 3]> {
+        gaussians.iter().map(|g| g.position_visibility.position).collect()
+    }
+
+    #[test]
+    fn decimating_to_the_full_count_keeps_the_cloud() {
+        let cloud = line_cloud(10);
+
+        for target_count in [10, 11, usize::MAX] {
+            let kept = decimate_cloud(&cloud, target_count, true);
+
+            assert_eq!(positions(&kept), positions(&cloud));
+            assert!(kept.iter().zip(&cloud).all(|(a, b)| a.scale_opacity.scale == b.scale_opacity.scale));
+        }
+
+        // Below the count the most opaque splats survive, in their original order
+        assert_eq!(positions(&decimate_cloud(&cloud, 2, false)), vec![[8.0, 0.0, 0.0], [9.0, 0.0, 0.0]]);
+    }
+
+    #[test]
+    fn ids_follow_splats_through_sort_decimate_and_merge() {
+        let cloud   = line_cloud(10);
+        let ids     = stable_ids(cloud.len());
+        let id_of   = |g: &Gaussian3d| g.position_visibility.position[0] as u32;
+
+        // Reverse the line, then keep the two most opaque splats
+        let (sorted, sorted_ids)    = sort_cloud_with_ids(&cloud, &ids, |g| -g.position_visibility.position[0]);
+        let (kept, kept_ids)        = decimate_cloud_with_ids(&sorted, &sorted_ids, 2, true);
+
+        assert_eq!(kept_ids, vec![9, 8]);
+        assert!(kept.iter().zip(&kept_ids).all(|(g, &id)| id_of(g) == id));
+
+        // Every pair of neighbors shares a cell and keeps the first one's id
+        let (merged, merged_ids) = merge_cloud_with_ids(&sorted, &sorted_ids, 2.0);
+        assert_eq!(merged.len(), 5);
+        assert_eq!(merged_ids, vec![9, 7, 5, 3, 1]);
+
+        let animated = animate_cloud_by_id(&kept, &kept_ids, 0.0, |g, id, _| {
+            assert_eq!(id_of(g), id as u32);
+            *g
+        });
+        assert_eq!(animated.len(), 2);
+    }
+}
@@ -8,6 +8,11 @@
 //!
 //! Make sure you load the shader as "tri_to_splat.wgsl" in your assets.
 
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Mutex,
+};
+
 use bevy::{
     core_pipeline::core_3d::graph::Core3d,
     ecs::query::QueryItem,
@@ -22,7 +27,7 @@ use bevy::{
             NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
         },
         render_resource::*,
-        renderer::{RenderContext, RenderDevice},
+        renderer::{RenderContext, RenderDevice, RenderQueue},
         Render, RenderApp, RenderSet,
     },
 };
@@ -32,9 +37,12 @@ use bevy_gaussian_splatting::{
     gaussian::formats::planar_3d::{
         PlanarStorageGaussian3d},
         sort::radix::RadixSortLabel,
+        GaussianSplattingPlugin,
         PlanarGaussian3dHandle
 };
 
+use crate::debug::hot_trace;
+
 
 
 
@@ -49,9 +57,9 @@ use bevy_gaussian_splatting::{
 pub struct TriToSplatParams {
     // Number of gaussians to process (max across clouds); informative for shader-side bounds.
     pub gaussian_count:   u32,
-    // Global elapsed time in seconds; used to drive hardcoded morph timelines.
+    // Seconds into the looping morph timeline; see `morph_timing`.
     pub elapsed_seconds:  f32,
-    // Hardcoded morph duration in seconds (can be overridden per-scene later).
+    // Morph duration in seconds, never zero; see `morph_timing`.
     pub duration_seconds: f32,
     // Padding to keep std140-like 16-byte alignment for the uniform struct.
     pub _pad:             f32,
@@ -68,6 +76,38 @@ pub struct TriToSplatParams {
 /// Index into the dynamic uniform buffer for the current view.
 pub type TriToSplatParamsIndex = DynamicUniformIndex<TriToSplatParams>;
 
+/// Length of the shader's looping morph timeline; must match `cycle` in `tri_to_splat.wgsl`.
+pub const MORPH_CYCLE_SECONDS: f32 = 5.0;
+
+/// Shortest `duration_seconds` uploaded; the shader divides by it.
+pub const MIN_MORPH_DURATION_SECONDS: f32 = 1e-3;
+
+/// `(elapsed_seconds, duration_seconds)` to upload for a morph of `duration` at `elapsed`.
+///
+/// Elapsed time is taken in f64, e.g. `Time::elapsed_secs_f64`, and wrapped into the looping
+/// cycle before narrowing, so the uploaded f32 stays precise once the app has run for hours.
+/// The duration is clamped to `MIN_MORPH_DURATION_SECONDS..=MORPH_CYCLE_SECONDS`, so the morph
+/// always completes and holds its end state until the cycle restarts. NaN or infinite elapsed
+/// times restart the cycle, and a NaN duration becomes the minimum.
+pub fn morph_timing(
+    elapsed:    f64,
+    duration:   f32,
+) -> (f32, f32) {
+
+    let elapsed = if elapsed.is_finite() {
+        elapsed.rem_euclid(MORPH_CYCLE_SECONDS as f64) as f32
+    } else {
+        0.0
+    };
+    let duration = if duration.is_nan() {
+        MIN_MORPH_DURATION_SECONDS
+    } else {
+        duration.clamp(MIN_MORPH_DURATION_SECONDS, MORPH_CYCLE_SECONDS)
+    };
+
+    (elapsed, duration)
+}
+
 
 
 
@@ -82,6 +122,43 @@ pub type TriToSplatParamsIndex = DynamicUniformIndex<TriToSplatParams>;
 pub struct TriToSplatGpu {
     pub bind_group_inputs:  BindGroup,
     pub workgroups:         UVec3,
+    /// The per-cloud counts uniform, rewritten while the cloud is being revealed
+    pub counts_buffer:      Buffer,
+    /// Revealed prefix currently in `counts_buffer`, so unchanged values aren't rewritten
+    pub visible:            u32,
+}
+
+
+
+/// Per-cloud constants bound at @group(0) @binding(3); must match TriToSplatCounts in the shader.
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Counts {
+    verts:          u32,
+    indices:        u32,
+    tris:           u32,
+    layers:         u32,
+    shell_offset:   f32,
+    /// Splats at this index and above are written invisible
+    visible:        u32,
+    _pad:           [u32; 2],
+}
+
+
+
+/// Progressive reveal for a GPU-converted cloud: only the first [`visible`](Self::visible)
+/// splats are shown, and the prefix grows by `rate` splats per second until the whole cloud is.
+#[derive(Component, Clone, Copy, Debug, ExtractComponent)]
+pub struct CloudReveal {
+    pub rate:       f32,
+    /// Splats revealed so far; fractional so slow rates still advance every frame
+    pub progress:   f32,
+}
+
+impl CloudReveal {
+    pub fn visible(&self) -> u32 {
+        self.progress as u32
+    }
 }
 
 
@@ -89,9 +166,20 @@ pub struct TriToSplatGpu {
 /// CPU-side inputs collected from a mesh, uploaded to GPU during prepare to back the inputs bind group.
 #[derive(Component, Clone, ExtractComponent)]
 pub struct TriToSplatCpuInput {
-    pub positions:  Vec<[f32; 4]>,
-    pub indices:    Vec<u32>,
-    pub tri_count:  u32,
+    pub positions:      Vec<[f32; 4]>,
+    pub indices:        Vec<u32>,
+    pub tri_count:      u32,
+    /// Shell layers per triangle (`MeshToGaussianMode::ThickShell`), at least 1
+    pub layers:         u32,
+    /// Distance between shell layers along the face normal
+    pub shell_offset:   f32,
+}
+
+impl TriToSplatCpuInput {
+    /// Splats written by the compute pass: one per triangle per layer.
+    pub fn splat_count(&self) -> u32 {
+        self.tri_count * self.layers.max(1)
+    }
 }
 
 
@@ -128,7 +216,7 @@ fn clear_tri_to_splat_jobs(
 ) {
 
     if !job_queue.jobs.is_empty() {
-        bevy::log::info!(
+        hot_trace!(
             "clear_tri_to_splat_jobs: clearing {} queued job(s)",
             job_queue.jobs.len()
         );
@@ -151,7 +239,7 @@ pub fn queue_planar_cloud_rw_bind_group(
     q:              Query<(Entity, &PlanarGaussian3dHandle)>,
 ) {
 
-    bevy::log::info!("queue_planar_cloud_rw_bind_group: begin");
+    hot_trace!("queue_planar_cloud_rw_bind_group: begin");
     
     let mut created = 0usize;
 
@@ -187,13 +275,13 @@ pub fn queue_planar_cloud_rw_bind_group(
             .entity(entity)
             .insert(PlanarStorageBindGroupRw { bind_group: bg });
 
-        bevy::log::info!("queue_planar_cloud_rw_bind_group: added PlanarStorageBindGroupRw to entity {entity:?}");
+        hot_trace!("queue_planar_cloud_rw_bind_group: added PlanarStorageBindGroupRw to entity {entity:?}");
 
         created += 1;
     }
 
     if created > 0 {
-        bevy::log::info!(
+        hot_trace!(
             "queue_planar_cloud_rw_bind_group: created {} bind groups",
             created
         );
@@ -213,23 +301,23 @@ pub fn queue_tri_to_splat_inputs(
     rd:             Res<RenderDevice>,
     pipe:           Res<TriToSplatPipeline>,
     mut job_queue:  ResMut<TriToSplatJobQueue>,
-    q:              Query<(Entity, &PlanarStorageBindGroupRw, &TriToSplatCpuInput)>,
+    q:              Query<(Entity, &PlanarStorageBindGroupRw, &TriToSplatCpuInput, Option<&CloudReveal>)>,
     existing_gpu:   Query<(), With<TriToSplatGpu>>, 
 ) {
 
-    bevy::log::info!("queue_tri_to_splat_inputs: candidates={}", q.iter().len());
+    hot_trace!("queue_tri_to_splat_inputs: candidates={}", q.iter().len());
 
     let mut created = 0usize;
 
-    for (entity, planar_rw, cpu) in &q {
+    for (entity, planar_rw, cpu, reveal) in &q {
 
         // Skip entities that already have TriToSplatGpu
         if existing_gpu.get(entity).is_ok() {
-            bevy::log::info!("queue_tri_to_splat_inputs: skipping entity {entity:?} - already has TriToSplatGpu");
+            hot_trace!("queue_tri_to_splat_inputs: skipping entity {entity:?} - already has TriToSplatGpu");
             continue;
         }
 
-        bevy::log::info!("queue_tri_to_splat_inputs: processing entity {entity:?}");
+        bevy::log::debug!("queue_tri_to_splat_inputs: processing entity {entity:?}");
 
         // Upload CPU arrays to GPU buffers
         let ro_flags    = BufferUsages::STORAGE | BufferUsages::COPY_DST;
@@ -248,21 +336,15 @@ pub fn queue_tri_to_splat_inputs(
             usage:      ro_flags,
         });
 
-        // Uniform: pack counts (verts, indices, tris)
-        #[repr(C)]
-        #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
-        struct Counts {
-            verts: u32,
-            indices: u32,
-            tris: u32,
-            _pad: u32,
-        }
-
+        // Uniform: pack counts (verts, indices, tris), the shell layout and the revealed prefix
         let counts = Counts {
-            verts:      cpu.positions.len() as u32,
-            indices:    cpu.indices.len() as u32,
-            tris:       cpu.tri_count,
-            _pad:       0,
+            verts:          cpu.positions.len() as u32,
+            indices:        cpu.indices.len() as u32,
+            tris:           cpu.tri_count,
+            layers:         cpu.layers.max(1),
+            shell_offset:   cpu.shell_offset,
+            visible:        reveal.map_or(u32::MAX, CloudReveal::visible),
+            _pad:           [0; 2],
         };
 
         let buf_counts = rd.create_buffer_with_data(&BufferInitDescriptor {
@@ -294,13 +376,14 @@ pub fn queue_tri_to_splat_inputs(
             ],
         );
 
-        // Workgroup sizing: match WGSL @workgroup_size(64, 1, 1)
-        let x = (cpu.tri_count + 63) / 64;
+        // Workgroup sizing: the same value is injected into the WGSL @workgroup_size
+        let x = cpu.splat_count().div_ceil(pipe.workgroup_size);
 
-        bevy::log::info!(
-            "queue_tri_to_splat_inputs: uploading {} verts / {} tris; dispatch x={}",
+        bevy::log::debug!(
+            "queue_tri_to_splat_inputs: uploading {} verts / {} tris x {} layers; dispatch x={}",
             cpu.positions.len(),
             cpu.tri_count,
+            cpu.layers.max(1),
             x.max(1)
         );
 
@@ -317,20 +400,63 @@ pub fn queue_tri_to_splat_inputs(
         commands.entity(entity).insert(TriToSplatGpu {
             bind_group_inputs: bind_group_inputs,
             workgroups,
+            counts_buffer: buf_counts,
+            visible: counts.visible,
         });
 
-        bevy::log::info!("queue_tri_to_splat_inputs: added TriToSplatGpu to entity {entity:?}");
+        bevy::log::debug!("queue_tri_to_splat_inputs: added TriToSplatGpu to entity {entity:?}");
 
         created += 1;
     }
 
     if created > 0 {
-        bevy::log::info!(
+        bevy::log::debug!(
             "queue_tri_to_splat_inputs: created {} inputs bind groups",
             created
         );
     }
 }
+
+
+
+/// Write the current reveal prefix of every revealing cloud into its counts uniform. Clouds
+/// whose prefix hasn't moved since the last write, including fully revealed ones, are skipped.
+pub fn update_tri_to_splat_reveal(
+    rq:     Res<RenderQueue>,
+    mut q:  Query<(&mut TriToSplatGpu, &CloudReveal)>,
+) {
+    for (mut gpu, reveal) in &mut q {
+        let visible = reveal.visible();
+        if visible == gpu.visible {
+            continue;
+        }
+
+        rq.write_buffer(
+            &gpu.counts_buffer,
+            std::mem::offset_of!(Counts, visible) as u64,
+            bytemuck::bytes_of(&visible),
+        );
+        gpu.visible = visible;
+    }
+}
+
+
+
+/// Grow every [`CloudReveal`] prefix by its rate, stopping once the whole cloud is shown.
+pub fn advance_cloud_reveals(
+    time:   Res<Time>,
+    mut q:  Query<(&mut CloudReveal, &TriToSplatCpuInput)>,
+) {
+    for (mut reveal, cpu) in &mut q {
+        let total = cpu.splat_count() as f32;
+        if reveal.progress < total {
+            reveal.progress = (reveal.progress + reveal.rate * time.delta_secs()).min(total);
+        }
+    }
+}
+
+
+
 /// Re-enqueue compute jobs every frame for entities that already have GPU bind groups.
 /// This makes the compute pass continuous without re-uploading buffers.
 pub fn requeue_existing_tri_to_splat_jobs(
@@ -348,7 +474,7 @@ pub fn requeue_existing_tri_to_splat_jobs(
     }
 
     if count > 0 {
-        bevy::log::info!(
+        hot_trace!(
             "requeue_existing_tri_to_splat_jobs: queued {} job(s) for this frame",
             count
         );
@@ -363,9 +489,21 @@ pub fn requeue_existing_tri_to_splat_jobs(
 
 // --------------------------------- Pipeline ----------------------------------
 
+/// Threads per workgroup for the tri_to_splat compute shader. Insert before adding
+/// `TriToSplatPlugin` to override the default; the pipeline reads it once at startup.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TriToSplatWorkgroupSize(pub u32);
+
+impl Default for TriToSplatWorkgroupSize {
+    fn default() -> Self {
+        Self(64)
+    }
+}
+
 #[derive(Resource)]
 pub struct TriToSplatPipeline {
     pub pipeline: CachedComputePipelineId,
+    pub workgroup_size: u32,               // single source of truth for shader + dispatch
     pub inputs_layout: BindGroupLayout,    // @group(0)
     pub params_layout: BindGroupLayout,    // @group(1) dynamic uniform
     pub planar_rw_layout: BindGroupLayout, // @group(2) - THIS IS NOW CORRECT
@@ -375,6 +513,13 @@ impl FromWorld for TriToSplatPipeline {
 
     fn from_world(world: &mut World) -> Self {
 
+        let workgroup_size = world
+            .get_resource::<TriToSplatWorkgroupSize>()
+            .copied()
+            .unwrap_or_default()
+            .0
+            .max(1);
+
         let rd           =  world.resource::<RenderDevice>();
         let asset_server =  world.resource::<AssetServer>();
 
@@ -513,13 +658,14 @@ impl FromWorld for TriToSplatPipeline {
                 ],
                 push_constant_ranges: vec![],
                 shader,
-                shader_defs: vec![],
+                shader_defs: vec![ShaderDefVal::UInt("WORKGROUP_SIZE".into(), workgroup_size)],
                 entry_point: "cs_main".into(),
                 zero_initialize_workgroup_memory: false,
             });
 
         Self {
             pipeline,
+            workgroup_size,
             inputs_layout,
             params_layout,
             planar_rw_layout, // Store our correct layout
@@ -533,6 +679,98 @@ impl FromWorld for TriToSplatPipeline {
 
 
 
+// ------------------------- Stats back to the main world -------------------------
+
+/// What the tri-to-splat compute pass did on the most recently rendered frame, copied into the
+/// main world every frame. Lets apps show progress or notice a pipeline that never gets ready
+/// (e.g. a missing `tri_to_splat.wgsl`), which otherwise only shows up in the logs.
+///
+/// Values lag the main world by a frame or so, since rendering runs behind it.
+#[derive(Resource, Debug, Clone, Default, PartialEq)]
+pub struct ComputeStats {
+    /// Conversion jobs queued for dispatch
+    pub jobs_queued:                    u32,
+    /// Jobs actually dispatched, 0 if the node didn't run (no pipeline yet, or no camera with
+    /// [`TriToSplatParams`])
+    pub jobs_dispatched:                u32,
+    /// The compute pipeline has compiled and can be dispatched
+    pub pipeline_ready:                 bool,
+    /// The compute pipeline failed to compile; it will not become ready
+    pub pipeline_failed:                bool,
+    /// Consecutive frames spent waiting for the pipeline, reset once it is ready
+    pub frames_waiting_for_pipeline:    u32,
+}
+
+/// Shared between the main and render worlds; the render world writes, the main world reads.
+/// Only ever holds a complete frame, see [`publish_tri_to_splat_stats`].
+#[derive(Resource, Clone, Default)]
+struct ComputeStatsChannel(Arc<Mutex<ComputeStats>>);
+
+/// The render frame's stats while they are being gathered, kept apart from the channel so the
+/// main world never sees a half-written frame.
+#[derive(Resource, Default)]
+struct PendingComputeStats {
+    stats:      ComputeStats,
+    /// Counted by the node, which only gets `&World`
+    dispatched: AtomicU32,
+}
+
+/// Records the pipeline state and queue length once jobs are queued for this frame. The node
+/// counts the jobs it dispatches on top.
+fn record_tri_to_splat_stats(
+    mut pending:    ResMut<PendingComputeStats>,
+    cache:          Res<PipelineCache>,
+    pipe:           Res<TriToSplatPipeline>,
+    job_queue:      Res<TriToSplatJobQueue>,
+) {
+
+    let state = cache.get_compute_pipeline_state(pipe.pipeline);
+
+    pending.dispatched = AtomicU32::new(0);
+
+    let stats = &mut pending.stats;
+    stats.jobs_queued       = job_queue.jobs.len() as u32;
+    stats.pipeline_ready    = matches!(state, CachedPipelineState::Ok(_));
+    stats.pipeline_failed   = matches!(state, CachedPipelineState::Err(_));
+
+    if stats.pipeline_ready {
+        stats.frames_waiting_for_pipeline = 0;
+    } else {
+        stats.frames_waiting_for_pipeline += 1;
+    }
+}
+
+/// Swaps the finished frame's stats into the channel once the render graph has run.
+fn publish_tri_to_splat_stats(
+    channel:    Res<ComputeStatsChannel>,
+    pending:    Res<PendingComputeStats>,
+) {
+
+    let snapshot = ComputeStats {
+        jobs_dispatched: pending.dispatched.load(Ordering::Relaxed),
+        ..pending.stats.clone()
+    };
+
+    let Ok(mut stats) = channel.0.lock() else { return };
+    *stats = snapshot;
+}
+
+/// Copies the render world's latest [`ComputeStats`] into the main world.
+fn sync_compute_stats(
+    channel:    Res<ComputeStatsChannel>,
+    mut stats:  ResMut<ComputeStats>,
+) {
+
+    let Ok(latest) = channel.0.lock() else { return };
+    stats.set_if_neq(latest.clone());
+}
+
+
+
+
+
+
+
 // ---------------------------------- Node -------------------------------------
 
 /// The compute node; consumes jobs queued during PrepareBindGroups (like the Game of Life example).
@@ -556,26 +794,26 @@ impl ViewNode for TriToSplatNode {
         world:                  &World,
     ) -> Result<(), NodeRunError> {
 
-        bevy::log::info!("TriToSplatNode: run() called");
+        hot_trace!("TriToSplatNode: run() called");
         
         let cache   = world.resource::<PipelineCache>();
         let pipe    = world.resource::<TriToSplatPipeline>();
 
         let Some(compute) = cache.get_compute_pipeline(pipe.pipeline) else {
-            bevy::log::warn!("TriToSplatNode: compute pipeline not ready yet");
+            bevy::log::debug!("TriToSplatNode: compute pipeline not ready yet");
             return Ok(());
         };
 
-        bevy::log::info!("TriToSplatNode: compute pipeline is ready");
+        hot_trace!("TriToSplatNode: compute pipeline is ready");
 
         let params_uniforms = world.resource::<ComponentUniforms<TriToSplatParams>>();
 
         let Some(params_binding) = params_uniforms.uniforms().binding() else {
-            bevy::log::warn!("TriToSplatNode: TriToSplatParams uniform buffer not initialized yet");
+            bevy::log::debug!("TriToSplatNode: TriToSplatParams uniform buffer not initialized yet");
             return Ok(());
         };
 
-        bevy::log::info!("TriToSplatNode: params uniform buffer is ready");
+        hot_trace!("TriToSplatNode: params uniform buffer is ready");
         
         let params_bg = rcx.render_device().create_bind_group(
             "tri_to_splat.params_bg",
@@ -597,7 +835,7 @@ impl ViewNode for TriToSplatNode {
         pass.set_pipeline(compute);
         pass.set_bind_group(1, &params_bg, &[params_ix.index()]);
 
-        bevy::log::info!("TriToSplatNode: bound params with index {}", params_ix.index());
+        hot_trace!("TriToSplatNode: bound params with index {}", params_ix.index());
 
         // Dispatch queued jobs
         let mut job_count = 0usize;
@@ -605,7 +843,7 @@ impl ViewNode for TriToSplatNode {
         if let Some(queue) = world.get_resource::<TriToSplatJobQueue>() {
 
             for job in &queue.jobs {
-                bevy::log::info!(
+                hot_trace!(
                     "TriToSplatNode: dispatching workgroups({}, {}, {})",
                     job.workgroups.x, job.workgroups.y, job.workgroups.z
                 );
@@ -619,10 +857,14 @@ impl ViewNode for TriToSplatNode {
             bevy::log::warn!("TriToSplatNode: TriToSplatJobQueue resource missing");
         }
 
+        if let Some(pending) = world.get_resource::<PendingComputeStats>() {
+            pending.dispatched.fetch_add(job_count as u32, Ordering::Relaxed);
+        }
+
         if job_count == 0 {
-            bevy::log::warn!("TriToSplatNode: no jobs to dispatch this frame - no entities found");
+            hot_trace!("TriToSplatNode: no jobs to dispatch this frame - no entities found");
         } else {
-            bevy::log::info!("TriToSplatNode: successfully dispatched {} job(s)", job_count);
+            hot_trace!("TriToSplatNode: successfully dispatched {} job(s)", job_count);
         }
 
         Ok(())
@@ -649,16 +891,26 @@ impl Plugin for TriToSplatPlugin {
             ExtractComponentPlugin::<TriToSplatParams>::default(),
             UniformComponentPlugin::<TriToSplatParams>::default(),
             ExtractComponentPlugin::<TriToSplatCpuInput>::default(),
+            ExtractComponentPlugin::<CloudReveal>::default(),
         ));
 
-        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+        app.add_systems(Update, advance_cloud_reveals);
+
+        let channel = ComputeStatsChannel::default();
+        app.init_resource::<ComputeStats>()
+            .insert_resource(channel.clone())
+            .add_systems(First, sync_compute_stats);
+
+        let Some(render_app) = splatting_render_app(app) else {
             return;
         };
 
-        bevy::log::info!("TriToSplatPlugin.build: configuring render systems and graph node");
+        bevy::log::debug!("TriToSplatPlugin.build: configuring render systems and graph node");
 
         render_app
             .init_resource::<TriToSplatJobQueue>()
+            .init_resource::<PendingComputeStats>()
+            .insert_resource(channel)
             .add_systems(
                 Render,
                 clear_tri_to_splat_jobs
@@ -679,8 +931,15 @@ impl Plugin for TriToSplatPlugin {
                     requeue_existing_tri_to_splat_jobs
                         .in_set(RenderSet::PrepareBindGroups)
                         .after(queue_tri_to_splat_inputs),
+                    update_tri_to_splat_reveal
+                        .in_set(RenderSet::PrepareBindGroups)
+                        .after(queue_tri_to_splat_inputs),
+                    record_tri_to_splat_stats
+                        .in_set(RenderSet::PrepareBindGroups)
+                        .after(requeue_existing_tri_to_splat_jobs),
                 ),
             )
+            .add_systems(Render, publish_tri_to_splat_stats.in_set(RenderSet::Cleanup))
             .add_render_graph_node::<ViewNodeRunner<TriToSplatNode>>(Core3d, TriToSplatNodeLabel)
             .add_render_graph_edges(
                 Core3d,
@@ -692,9 +951,74 @@ impl Plugin for TriToSplatPlugin {
     }
 
     fn finish(&self, app: &mut App) {
-        if let Some(render_app) = app.get_sub_app_mut(RenderApp) {
-            bevy::log::info!("TriToSplatPlugin.finish: initializing TriToSplatPipeline resource");
-            render_app.init_resource::<TriToSplatPipeline>();
-        }
+        bevy::log::debug!("TriToSplatPlugin.finish: initializing TriToSplatPipeline resource");
+        init_tri_to_splat_pipeline(app);
+    }
+}
+
+/// The render app, if `GaussianSplattingPlugin` has been added to set it up. The render graph
+/// edges into `RadixSortLabel` and the planar storage render assets come from that plugin, so
+/// without it (e.g. `GenGaussianPlugin::without_renderer()` or no `RenderPlugin`) only the
+/// main-world half of the GPU plugins is added.
+pub(crate) fn splatting_render_app(app: &mut App) -> Option<&mut SubApp> {
+    if !app.is_plugin_added::<GaussianSplattingPlugin>() {
+        return None;
+    }
+
+    app.get_sub_app_mut(RenderApp)
+}
+
+/// Create `TriToSplatPipeline` in the render app with the main world's
+/// `TriToSplatWorkgroupSize`, unless it already exists. Every plugin that borrows its planar RW
+/// layout goes through here, so the pipeline doesn't depend on which one finishes first.
+pub(crate) fn init_tri_to_splat_pipeline(app: &mut App) {
+    let workgroup_size = app
+        .world()
+        .get_resource::<TriToSplatWorkgroupSize>()
+        .copied()
+        .unwrap_or_default();
+
+    let Some(render_app) = splatting_render_app(app) else {
+        return;
+    };
+
+    if !render_app.world().contains_resource::<TriToSplatPipeline>() {
+        render_app.insert_resource(workgroup_size);
+        render_app.init_resource::<TriToSplatPipeline>();
+    }
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn morph_timing_wraps_elapsed_into_the_cycle() {
+        assert_eq!(morph_timing(1.0, 3.0), (1.0, 3.0));
+        assert_eq!(morph_timing(6.5, 3.0), (1.5, 3.0));
+        assert_eq!(morph_timing(0.0, 3.0), (0.0, 3.0));
+        assert_eq!(morph_timing(-1.0, 3.0).0, 4.0);
+
+        assert_eq!(morph_timing(f64::NAN, 3.0).0, 0.0);
+        assert_eq!(morph_timing(f64::INFINITY, 3.0).0, 0.0);
+        assert_eq!(morph_timing(f64::NEG_INFINITY, 3.0).0, 0.0);
+
+        // Months in, the time within the cycle is still exact
+        assert_eq!(morph_timing(1e7 + 0.25, 3.0).0, 0.25);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn morph_timing_clamps_the_duration() {
+        assert_eq!(morph_timing(1.0, 0.0).1, MIN_MORPH_DURATION_SECONDS);
+        assert_eq!(morph_timing(1.0, -2.0).1, MIN_MORPH_DURATION_SECONDS);
+        assert_eq!(morph_timing(1.0, f32::NAN).1, MIN_MORPH_DURATION_SECONDS);
+        assert_eq!(morph_timing(1.0, f32::INFINITY).1, MORPH_CYCLE_SECONDS);
+        assert_eq!(morph_timing(1.0, 1e9).1, MORPH_CYCLE_SECONDS);
+    }
+}
@@ -0,0 +1,788 @@
+// Point cloud import and cloud export. Reads plain point-cloud files into the slices
+// `points_to_gaussians` takes, and writes clouds as standard 3DGS PLY files or as plain text
+// (CSV, and JSON with the `serde` feature) for diffing and bug reports.
+
+use std::io::{BufWriter, Error, ErrorKind, Result, Write};
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_gaussian_splatting::{material::spherical_harmonics::SH_COEFF_COUNT, Gaussian3d, PlanarGaussian3d};
+#[cfg(feature = "serde")]
+use bevy_gaussian_splatting::{
+    gaussian::f32::{PositionVisibility, Rotation, ScaleOpacity},
+    SphericalHarmonicCoefficients,
+};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::gaussian::builder::PlanarGaussian3dBuilder;
+
+
+
+
+
+
+
+/// Positions, optional normals and optional RGB colors in 0..1, one entry per point.
+pub type ImportedPoints = (Vec<Vec3>, Option<Vec<Vec3>>, Option<Vec<[f32; 3]>>);
+
+
+
+/// Read the `vertex` element of a PLY file.
+///
+/// Positions come from `x`, `y`, `z`. Normals are returned when `nx`, `ny`, `nz` are all present,
+/// and colors when `red`, `green`, `blue` are: integer channels are normalized by their type's
+/// maximum, float channels are taken as they are. Feed the colors to
+/// `points_to_gaussians_with_colors` with `ColorMode::PerPoint`.
+pub fn import_points_ply(
+    path: impl AsRef<Path>
+) -> Result<ImportedPoints> {
+
+    parse_points_ply(&std::fs::read(path)?)
+}
+
+
+
+/// [`import_points_ply`] over the bytes of a PLY file already in memory.
+///
+/// Supports the `ascii`, `binary_little_endian` and `binary_big_endian` formats. Elements before
+/// `vertex` are skipped, including list properties such as face indices.
+pub fn parse_points_ply(
+    bytes: &[u8]
+) -> Result<ImportedPoints> {
+
+    let (header, body)  = split_header(bytes)?;
+    let vertex_index    = header.elements
+        .iter()
+        .position(|e| e.name == "vertex")
+        .ok_or_else(|| invalid("PLY has no vertex element"))?;
+
+    let vertex          = &header.elements[vertex_index];
+    let find            = |name: &str| vertex.properties.iter().position(|p| p.name == name);
+
+    let position_idx    = [find("x"), find("y"), find("z")];
+    let normal_idx      = [find("nx"), find("ny"), find("nz")];
+    let color_idx       = [find("red"), find("green"), find("blue")];
+
+    let [Some(px), Some(py), Some(pz)] = position_idx else {
+        return Err(invalid("PLY vertex element has no x, y, z properties"));
+    };
+
+    let normal_idx  = match normal_idx { [Some(x), Some(y), Some(z)] => Some([x, y, z]), _ => None };
+    let color_idx   = match color_idx { [Some(r), Some(g), Some(b)] => Some([r, g, b]), _ => None };
+
+    let mut reader = ValueReader::new(header.format, body);
+
+    // An element without properties takes no bytes, however large its count
+    for element in header.elements[..vertex_index].iter().filter(|e| !e.properties.is_empty()) {
+        for _ in 0..element.count {
+            for property in &element.properties {
+                reader.skip_property(property)?;
+            }
+        }
+    }
+
+    // Every vertex takes at least a byte, so a count beyond the body is a broken header; don't
+    // trust it for allocation and let the reads fail instead
+    let capacity        = vertex.count.min(body.len());
+    let mut positions   = Vec::with_capacity(capacity);
+    let mut normals     = normal_idx.map(|_| Vec::with_capacity(capacity));
+    let mut colors      = color_idx.map(|_| Vec::with_capacity(capacity));
+    let mut row         = vec![0.0; vertex.properties.len()];
+
+    for _ in 0..vertex.count {
+        for (value, property) in row.iter_mut().zip(&vertex.properties) {
+            *value = match property.kind {
+                PropertyKind::Scalar(scalar) => reader.read(scalar)?,
+                // Lists on a vertex carry nothing we import
+                PropertyKind::List { .. } => { reader.skip_property(property)?; 0.0 }
+            };
+        }
+
+        positions.push(Vec3::new(row[px] as f32, row[py] as f32, row[pz] as f32));
+
+        if let (Some(normals), Some([x, y, z])) = (normals.as_mut(), normal_idx) {
+            normals.push(Vec3::new(row[x] as f32, row[y] as f32, row[z] as f32));
+        }
+
+        if let (Some(colors), Some(idx)) = (colors.as_mut(), color_idx) {
+            colors.push(idx.map(|i| match vertex.properties[i].kind {
+                PropertyKind::Scalar(scalar) => (row[i] / scalar.color_max()) as f32,
+                PropertyKind::List { .. } => 0.0,
+            }));
+        }
+    }
+
+    Ok((positions, normals, colors))
+}
+
+
+
+
+
+
+
+/// Write `gaussians` to a binary 3DGS PLY file at `path`.
+///
+/// See [`write_cloud_ply`] for the layout.
+pub fn export_cloud_ply(
+    path:       impl AsRef<Path>,
+    gaussians:  &[Gaussian3d],
+    normals:    Option<&[Vec3]>,
+) -> Result<()> {
+
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    write_cloud_ply(&mut writer, gaussians, normals)?;
+    writer.flush()
+}
+
+
+
+/// Write `gaussians` as a `binary_little_endian` PLY in the layout of the reference 3DGS
+/// implementation, which `bevy_gaussian_splatting` and most splat viewers load.
+///
+/// Opacity is stored as a logit and scale as a log, as those loaders expect, and the
+/// higher-order SH coefficients are written channel by channel. Rotations are written as stored,
+/// scalar first, which is also the format's `rot_0..3` order. Invisible splats are left out,
+/// since the format has no visibility flag.
+///
+/// `normals` holds one normal per gaussian, as returned by `mesh_to_gaussians_with_normals` and
+/// `sdf_to_gaussians_with_normals`; without them, or for gaussians past their end, zero is written.
+pub fn write_cloud_ply(
+    mut writer: impl Write,
+    gaussians:  &[Gaussian3d],
+    normals:    Option<&[Vec3]>,
+) -> Result<()> {
+
+    let rest_per_channel    = SH_COEFF_COUNT / 3 - 1;
+    let normals             = normals.unwrap_or_default();
+    let visible: Vec<_>     = gaussians
+        .iter()
+        .enumerate()
+        .filter(|(_, g)| g.position_visibility.visibility > 0.0)
+        .collect();
+
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format binary_little_endian 1.0")?;
+    writeln!(writer, "element vertex {}", visible.len())?;
+    for name in ["x", "y", "z", "nx", "ny", "nz", "f_dc_0", "f_dc_1", "f_dc_2"] {
+        writeln!(writer, "property float {name}")?;
+    }
+    for i in 0..rest_per_channel * 3 {
+        writeln!(writer, "property float f_rest_{i}")?;
+    }
+    for name in ["opacity", "scale_0", "scale_1", "scale_2", "rot_0", "rot_1", "rot_2", "rot_3"] {
+        writeln!(writer, "property float {name}")?;
+    }
+    writeln!(writer, "end_header")?;
+
+    for (i, g) in visible {
+        let sh      = &g.spherical_harmonic.coefficients;
+        let opacity = g.scale_opacity.opacity.clamp(1e-6, 1.0 - 1e-6);
+        let normal  = normals.get(i).copied().unwrap_or(Vec3::ZERO);
+
+        let mut row = Vec::with_capacity(17 + rest_per_channel * 3);
+        row.extend(g.position_visibility.position);
+        row.extend(normal.to_array());
+        row.extend(&sh[..3]);
+
+        // Coefficients are interleaved per channel in memory and grouped by channel on disk
+        for channel in 0..3 {
+            for k in 1..=rest_per_channel {
+                row.push(sh[k * 3 + channel]);
+            }
+        }
+
+        row.push((opacity / (1.0 - opacity)).ln());
+        row.extend(g.scale_opacity.scale.map(|s| s.abs().max(f32::MIN_POSITIVE).ln()));
+        row.extend(g.rotation.rotation);
+
+        for value in row {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+
+
+
+
+
+
+/// Write `cloud` as CSV to `path`. See [`write_cloud_csv`].
+pub fn export_cloud_csv(
+    path:   impl AsRef<Path>,
+    cloud:  &PlanarGaussian3d,
+) -> Result<()> {
+
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    write_cloud_csv(&mut writer, cloud)?;
+    writer.flush()
+}
+
+
+
+/// Write one row per splat, invisible ones included, with a header row:
+/// `x,y,z,scale_0..2,opacity,rot_0..3,sh_dc_0..2,visibility`.
+///
+/// Values are written as they are stored (linear opacity and scale, raw DC coefficients), in
+/// Rust's shortest round-trip float format, so equal clouds give identical text.
+pub fn write_cloud_csv(
+    mut writer: impl Write,
+    cloud:      &PlanarGaussian3d,
+) -> Result<()> {
+
+    writeln!(
+        writer,
+        "x,y,z,scale_0,scale_1,scale_2,opacity,rot_0,rot_1,rot_2,rot_3,sh_dc_0,sh_dc_1,sh_dc_2,visibility"
+    )?;
+
+    let splats = cloud.position_visibility
+        .iter()
+        .zip(&cloud.scale_opacity)
+        .zip(&cloud.rotation)
+        .zip(&cloud.spherical_harmonic);
+
+    for (((pv, so), rot), sh) in splats {
+        let row: Vec<String> = pv.position
+            .iter()
+            .chain(&so.scale)
+            .chain([&so.opacity])
+            .chain(&rot.rotation)
+            .chain(&sh.coefficients[..3])
+            .chain([&pv.visibility])
+            .map(f32::to_string)
+            .collect();
+
+        writeln!(writer, "{}", row.join(","))?;
+    }
+
+    Ok(())
+}
+
+
+
+/// One splat in the JSON export, with values as stored in the cloud.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SplatRecord {
+    pos:        [f32; 3],
+    scale:      [f32; 3],
+    opacity:    f32,
+    rot:        [f32; 4],
+    sh_dc:      [f32; 3],
+    #[serde(default = "visible")]
+    visibility: f32,
+}
+
+#[cfg(feature = "serde")]
+fn visible() -> f32 {
+    1.0
+}
+
+
+
+/// Write `cloud` as JSON to `path`. See [`write_cloud_json`].
+#[cfg(feature = "serde")]
+pub fn export_cloud_json(
+    path:   impl AsRef<Path>,
+    cloud:  &PlanarGaussian3d,
+) -> Result<()> {
+
+    let mut writer = BufWriter::new(std::fs::File::create(path)?);
+    write_cloud_json(&mut writer, cloud)?;
+    writer.flush()
+}
+
+
+
+/// Write `cloud` as a pretty-printed JSON array with one
+/// `{pos, scale, opacity, rot, sh_dc, visibility}` object per splat, invisible ones included.
+///
+/// Only the DC term of the spherical harmonics is written, so view-dependent color is lost on
+/// a round trip through [`import_cloud_json`].
+#[cfg(feature = "serde")]
+pub fn write_cloud_json(
+    writer: impl Write,
+    cloud:  &PlanarGaussian3d,
+) -> Result<()> {
+
+    let records: Vec<SplatRecord> = cloud.position_visibility
+        .iter()
+        .zip(&cloud.scale_opacity)
+        .zip(&cloud.rotation)
+        .zip(&cloud.spherical_harmonic)
+        .map(|(((pv, so), rot), sh)| SplatRecord {
+            pos:        pv.position,
+            scale:      so.scale,
+            opacity:    so.opacity,
+            rot:        rot.rotation,
+            sh_dc:      [sh.coefficients[0], sh.coefficients[1], sh.coefficients[2]],
+            visibility: pv.visibility,
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(writer, &records).map_err(Error::from)
+}
+
+
+
+/// Read a cloud written by [`export_cloud_json`]. A missing `visibility` counts as visible and
+/// the higher-order SH coefficients are zero.
+#[cfg(feature = "serde")]
+pub fn import_cloud_json(
+    path: impl AsRef<Path>
+) -> Result<PlanarGaussian3d> {
+
+    parse_cloud_json(&std::fs::read(path)?)
+}
+
+
+
+/// [`import_cloud_json`] over JSON already in memory.
+#[cfg(feature = "serde")]
+pub fn parse_cloud_json(
+    bytes: &[u8]
+) -> Result<PlanarGaussian3d> {
+
+    let records: Vec<SplatRecord> = serde_json::from_slice(bytes).map_err(Error::from)?;
+
+    let mut builder = PlanarGaussian3dBuilder::with_capacity(records.len());
+
+    for record in records {
+        let mut sh = SphericalHarmonicCoefficients { coefficients: [0.0; SH_COEFF_COUNT] };
+        sh.coefficients[..3].copy_from_slice(&record.sh_dc);
+
+        builder.push(
+            PositionVisibility  { position: record.pos, visibility: record.visibility },
+            sh,
+            Rotation            { rotation: record.rot },
+            ScaleOpacity        { scale: record.scale, opacity: record.opacity },
+        );
+    }
+
+    Ok(builder.finish())
+}
+
+
+
+
+
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum PropertyKind {
+    Scalar(Scalar),
+    List { count: Scalar, item: Scalar },
+}
+
+struct Property {
+    name:   String,
+    kind:   PropertyKind,
+}
+
+struct Element {
+    name:       String,
+    count:      usize,
+    properties: Vec<Property>,
+}
+
+struct Header {
+    format:     Format,
+    elements:   Vec<Element>,
+}
+
+
+
+impl Scalar {
+
+    fn parse(
+        name: &str
+    ) -> Result<Self> {
+
+        Ok(match name {
+            "char"   | "int8"       => Self::I8,
+            "uchar"  | "uint8"      => Self::U8,
+            "short"  | "int16"      => Self::I16,
+            "ushort" | "uint16"     => Self::U16,
+            "int"    | "int32"      => Self::I32,
+            "uint"   | "uint32"     => Self::U32,
+            "float"  | "float32"    => Self::F32,
+            "double" | "float64"    => Self::F64,
+            _ => return Err(invalid(&format!("unknown PLY property type '{name}'"))),
+        })
+    }
+
+    fn size(
+        self
+    ) -> usize {
+
+        match self {
+            Self::I8  | Self::U8                => 1,
+            Self::I16 | Self::U16               => 2,
+            Self::I32 | Self::U32 | Self::F32   => 4,
+            Self::F64                           => 8,
+        }
+    }
+
+    // Value that maps to full intensity when the scalar stores a color channel
+    fn color_max(
+        self
+    ) -> f64 {
+
+        match self {
+            Self::I8                => i8::MAX as f64,
+            Self::U8                => u8::MAX as f64,
+            Self::I16               => i16::MAX as f64,
+            Self::U16               => u16::MAX as f64,
+            Self::I32               => i32::MAX as f64,
+            Self::U32               => u32::MAX as f64,
+            Self::F32 | Self::F64   => 1.0,
+        }
+    }
+}
+
+
+
+// Splits the file at the end of the header line and parses everything before it.
+fn split_header(
+    bytes: &[u8]
+) -> Result<(Header, &[u8])> {
+
+    const END: &[u8] = b"end_header";
+
+    let end = bytes
+        .windows(END.len())
+        .position(|w| w == END)
+        .ok_or_else(|| invalid("PLY header is not terminated by end_header"))?;
+
+    let body_start = bytes[end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |p| end + p + 1);
+
+    let text = std::str::from_utf8(&bytes[..end]).map_err(|_| invalid("PLY header is not UTF-8"))?;
+
+    let mut lines = text.lines().map(str::trim);
+    if lines.next() != Some("ply") {
+        return Err(invalid("missing 'ply' magic"));
+    }
+
+    let mut format      = None;
+    let mut elements    = Vec::<Element>::new();
+
+    for line in lines {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["format", name, ..] => {
+                format = Some(match *name {
+                    "ascii"                 => Format::Ascii,
+                    "binary_little_endian"  => Format::BinaryLittleEndian,
+                    "binary_big_endian"     => Format::BinaryBigEndian,
+                    _ => return Err(invalid(&format!("unknown PLY format '{name}'"))),
+                });
+            }
+
+            ["element", name, count] => {
+                elements.push(Element {
+                    name:       name.to_string(),
+                    count:      count.parse().map_err(|_| invalid("bad PLY element count"))?,
+                    properties: Vec::new(),
+                });
+            }
+
+            ["property", "list", count, item, name] => {
+                let element = elements.last_mut().ok_or_else(|| invalid("PLY property before any element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind: PropertyKind::List { count: Scalar::parse(count)?, item: Scalar::parse(item)? },
+                });
+            }
+
+            ["property", kind, name] => {
+                let element = elements.last_mut().ok_or_else(|| invalid("PLY property before any element"))?;
+                element.properties.push(Property {
+                    name: name.to_string(),
+                    kind: PropertyKind::Scalar(Scalar::parse(kind)?),
+                });
+            }
+
+            // comment, obj_info and blank lines
+            _ => {}
+        }
+    }
+
+    let format = format.ok_or_else(|| invalid("PLY header has no format line"))?;
+
+    Ok((Header { format, elements }, &bytes[body_start..]))
+}
+
+
+
+// Sequential reader over the PLY body in either encoding.
+struct ValueReader<'a> {
+    format: Format,
+    bytes:  &'a [u8],
+    cursor: usize,
+}
+
+impl<'a> ValueReader<'a> {
+
+    fn new(
+        format: Format,
+        bytes:  &'a [u8],
+    ) -> Self {
+
+        Self { format, bytes, cursor: 0 }
+    }
+
+    fn read(
+        &mut self,
+        scalar: Scalar,
+    ) -> Result<f64> {
+
+        if self.format == Format::Ascii {
+            return self.next_word()?
+                .parse()
+                .map_err(|_| invalid("bad number in PLY body"));
+        }
+
+        let size    = scalar.size();
+        let bytes   = self.bytes
+            .get(self.cursor..self.cursor + size)
+            .ok_or_else(|| Error::from(ErrorKind::UnexpectedEof))?;
+        self.cursor += size;
+
+        let mut buf = [0u8; 8];
+        buf[..size].copy_from_slice(bytes);
+        if self.format == Format::BinaryBigEndian {
+            buf[..size].reverse();
+        }
+
+        Ok(match scalar {
+            Scalar::I8  => buf[0] as i8 as f64,
+            Scalar::U8  => buf[0] as f64,
+            Scalar::I16 => i16::from_le_bytes([buf[0], buf[1]]) as f64,
+            Scalar::U16 => u16::from_le_bytes([buf[0], buf[1]]) as f64,
+            Scalar::I32 => i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            Scalar::U32 => u32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            Scalar::F32 => f32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]) as f64,
+            Scalar::F64 => f64::from_le_bytes(buf),
+        })
+    }
+
+    fn skip_property(
+        &mut self,
+        property: &Property,
+    ) -> Result<()> {
+
+        match property.kind {
+            PropertyKind::Scalar(scalar) => {
+                self.read(scalar)?;
+            }
+            PropertyKind::List { count, item } => {
+                let n = self.read(count)? as usize;
+                for _ in 0..n {
+                    self.read(item)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn next_word(
+        &mut self
+    ) -> Result<&'a str> {
+
+        let rest    = &self.bytes[self.cursor..];
+        let start   = rest
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .ok_or_else(|| Error::from(ErrorKind::UnexpectedEof))?;
+        let len     = rest[start..]
+            .iter()
+            .position(|b| b.is_ascii_whitespace())
+            .unwrap_or(rest.len() - start);
+
+        self.cursor += start + len;
+
+        std::str::from_utf8(&rest[start..start + len]).map_err(|_| invalid("PLY body is not UTF-8"))
+    }
+}
+
+
+
+fn invalid(
+    message: &str
+) -> Error {
+
+    Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gaussian::cpu_transform::{stored_rotation, to_planar, unit_rotation};
+
+    const ASCII_HEADER: &str = "ply\nformat ascii 1.0\nelement vertex {count}\nproperty float x\n\
+        property float y\nproperty float z\nproperty uchar red\nproperty uchar green\n\
+        property uchar blue\nend_header\n";
+
+    fn ascii_ply(
+        count:  &str,
+        body:   &str,
+    ) -> Vec<u8> {
+        (ASCII_HEADER.replace("{count}", count) + body).into_bytes()
+    }
+
+    #[test]
+    fn parses_ascii_points() {
+        let (positions, normals, colors) = parse_points_ply(&ascii_ply("2", "1 2 3 255 0 0\n-1 0 0.5 0 51 255\n")).unwrap();
+
+        assert_eq!(positions, vec![Vec3::new(1.0, 2.0, 3.0), Vec3::new(-1.0, 0.0, 0.5)]);
+        assert!(normals.is_none());
+        assert_eq!(colors.unwrap(), vec![[1.0, 0.0, 0.0], [0.0, 0.2, 1.0]]);
+    }
+
+    #[test]
+    fn truncated_body_is_an_error() {
+        let error = parse_points_ply(&ascii_ply("2", "1 2 3 255 0 0\n-1 0")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn oversized_vertex_count_is_an_error() {
+        let ply = ascii_ply(&usize::MAX.to_string(), "1 2 3 255 0 0\n");
+        assert!(parse_points_ply(&ply).is_err());
+
+        let mut binary = b"ply\nformat binary_little_endian 1.0\nelement vertex 4000000000000\n\
+            property float x\nproperty float y\nproperty float z\nend_header\n".to_vec();
+        binary.extend(1.0f32.to_le_bytes().repeat(3));
+        assert!(parse_points_ply(&binary).is_err());
+    }
+
+    // rot_0..3 of a PLY holding a single splat, which are the last four floats of the file
+    fn ply_rotation(
+        g: Gaussian3d
+    ) -> Vec<f32> {
+
+        let mut ply = Vec::new();
+        write_cloud_ply(&mut ply, &[g], None).unwrap();
+
+        ply[ply.len() - 16..]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn cloud_ply_writes_the_rotation_scalar_first() {
+        let rotation = Quat::from_euler(EulerRot::XYZ, 0.3, -1.2, 2.0);
+
+        let mut g = Gaussian3d::default();
+        g.position_visibility.visibility    = 1.0;
+        g.rotation.rotation                 = stored_rotation(rotation);
+
+        let tail = ply_rotation(g);
+        assert_eq!(tail, vec![rotation.w, rotation.x, rotation.y, rotation.z]);
+
+        let read_back = Quat::from_xyzw(tail[1], tail[2], tail[3], tail[0]);
+        assert!((read_back * Vec3::X).abs_diff_eq(rotation * Vec3::X, 1e-6));
+    }
+
+    #[test]
+    fn cloud_ply_keeps_gpu_written_rotations() {
+        // The compute shaders and the Beat Cauldron grid write identity as [1, 0, 0, 0]
+        let mut g = Gaussian3d::default();
+        g.position_visibility.visibility    = 1.0;
+        g.rotation.rotation                 = [1.0, 0.0, 0.0, 0.0];
+
+        assert_eq!(ply_rotation(g), vec![1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(unit_rotation(&g), Quat::IDENTITY);
+    }
+
+    // A visible splat with distinct values everywhere and a hidden one
+    fn two_splat_cloud() -> PlanarGaussian3d {
+        let mut a = Gaussian3d::default();
+        a.position_visibility.position      = [1.0, -2.5, 0.125];
+        a.position_visibility.visibility    = 1.0;
+        a.scale_opacity.scale               = [0.5, 0.25, 0.75];
+        a.scale_opacity.opacity             = 0.8;
+        a.rotation.rotation                 = [0.0, 0.6, 0.0, 0.8];
+        a.spherical_harmonic.coefficients[..3].copy_from_slice(&[0.1, 0.2, 0.3]);
+
+        let mut b = a;
+        b.position_visibility.position      = [-4.0, 0.0, 3.0];
+        b.position_visibility.visibility    = 0.0;
+
+        to_planar(&[a, b])
+    }
+
+    #[test]
+    fn csv_rows_follow_the_header() {
+        let mut csv = Vec::new();
+        write_cloud_csv(&mut csv, &two_splat_cloud()).unwrap();
+
+        let text            = String::from_utf8(csv).unwrap();
+        let lines: Vec<_>   = text.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].split(',').count(), 15);
+        assert_eq!(lines[1], "1,-2.5,0.125,0.5,0.25,0.75,0.8,0,0.6,0,0.8,0.1,0.2,0.3,1");
+        assert_eq!(lines[2], "-4,0,3,0.5,0.25,0.75,0.8,0,0.6,0,0.8,0.1,0.2,0.3,0");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_round_trips_the_stored_values() {
+        let cloud = two_splat_cloud();
+
+        let mut json = Vec::new();
+        write_cloud_json(&mut json, &cloud).unwrap();
+        let read_back = parse_cloud_json(&json).unwrap();
+
+        assert_eq!(read_back.position_visibility, cloud.position_visibility);
+        assert_eq!(read_back.scale_opacity, cloud.scale_opacity);
+        assert_eq!(read_back.rotation, cloud.rotation);
+
+        for (read, written) in read_back.spherical_harmonic.iter().zip(&cloud.spherical_harmonic) {
+            assert_eq!(read.coefficients[..3], written.coefficients[..3]);
+            assert!(read.coefficients[3..].iter().all(|&c| c == 0.0));
+        }
+
+        // Visibility defaults to visible when it is left out
+        let minimal = br#"[{"pos":[1,2,3],"scale":[1,1,1],"opacity":1,"rot":[0,0,0,1],"sh_dc":[0,0,0]}]"#;
+        assert_eq!(parse_cloud_json(minimal).unwrap().position_visibility[0].visibility, 1.0);
+    }
+}
+
@@ -0,0 +1,152 @@
+// Distance-based level of detail for static clouds, swapping between pre-decimated variants.
+
+use bevy::prelude::*;
+use bevy_gaussian_splatting::{Gaussian3d, PlanarGaussian3d, PlanarGaussian3dHandle};
+
+use crate::gaussian::{
+    cpu_transform::{decimate_cloud, to_planar},
+    stats::compute_stats,
+};
+
+
+
+
+
+
+
+/// One variant of a [`CloudLod`], used while the camera is at most `max_distance` away.
+#[derive(Debug, Clone, Reflect)]
+pub struct CloudLodLevel {
+    pub handle:         Handle<PlanarGaussian3d>,
+    pub max_distance:   f32,
+}
+
+
+
+/// Pre-built variants of a cloud, from full detail to coarsest, swapped into the entity's
+/// `PlanarGaussian3dHandle` by distance to the nearest 3D camera.
+///
+/// The first level whose `max_distance` covers the camera distance is shown; past the last
+/// threshold the last level stays active. Distances are measured to `center`, in the entity's
+/// local space.
+#[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component)]
+pub struct CloudLod {
+    pub levels:     Vec<CloudLodLevel>,
+    pub center:     Vec3,
+    /// Fraction of a threshold the camera must move past it before switching back to a finer
+    /// level, so hovering at a boundary doesn't swap every frame
+    pub hysteresis: f32,
+    active:         Option<usize>,
+}
+
+impl CloudLod {
+
+    /// LOD over existing variants, sorted by `max_distance`.
+    pub fn new(
+        mut levels: Vec<CloudLodLevel>,
+        center:     Vec3,
+    ) -> Self {
+
+        levels.sort_by(|a, b| a.max_distance.total_cmp(&b.max_distance));
+
+        Self { levels, center, hysteresis: 0.05, active: None }
+    }
+
+
+
+    /// Build the variants once from a full-detail cloud. Each `(target_count, max_distance)` pair
+    /// adds a level decimated to `target_count` splats with [`decimate_cloud`], compensating
+    /// coverage; a count at or above the cloud's size keeps it whole. The center is the middle
+    /// of the cloud's bounds.
+    pub fn from_decimation(
+        gaussians:  &[Gaussian3d],
+        levels:     &[(usize, f32)],
+        clouds:     &mut Assets<PlanarGaussian3d>,
+    ) -> Self {
+
+        let full    = to_planar(gaussians);
+        let center  = compute_stats(&full).center();
+
+        let levels = levels
+            .iter()
+            .map(|&(target_count, max_distance)| CloudLodLevel {
+                handle: clouds.add(to_planar(&decimate_cloud(gaussians, target_count, true))),
+                max_distance,
+            })
+            .collect();
+
+        Self::new(levels, center)
+    }
+
+
+
+    /// Index of the level currently shown, `None` until the first selection.
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+
+
+    /// Level to show at `distance`, given the one shown now.
+    pub fn level_for_distance(
+        &self,
+        distance: f32,
+    ) -> usize {
+
+        let last    = self.levels.len().saturating_sub(1);
+        let target  = self.levels
+            .iter()
+            .position(|level| distance <= level.max_distance)
+            .unwrap_or(last);
+
+        // Only refine once clearly inside a finer level's range
+        match self.active {
+            Some(active) if target < active && active < self.levels.len() => {
+                let shrink = 1.0 - self.hysteresis.clamp(0.0, 1.0);
+
+                self.levels[target..active]
+                    .iter()
+                    .position(|level| distance <= level.max_distance * shrink)
+                    .map_or(active, |i| target + i)
+            }
+            _ => target,
+        }
+    }
+}
+
+
+
+/// Swaps each [`CloudLod`] entity's cloud handle to the level for its distance to the nearest
+/// active 3D camera. Handles are only written when the level changes.
+pub fn select_cloud_lods(
+    camera_q:   Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    mut lod_q:  Query<(&mut CloudLod, &GlobalTransform, &mut PlanarGaussian3dHandle)>,
+) {
+
+    let cameras: Vec<Vec3> = camera_q
+        .iter()
+        .filter(|(camera, _)| camera.is_active)
+        .map(|(_, transform)| transform.translation())
+        .collect();
+
+    if cameras.is_empty() {
+        return;
+    }
+
+    for (mut lod, transform, mut handle) in &mut lod_q {
+
+        if lod.levels.is_empty() {
+            continue;
+        }
+
+        let center      = transform.transform_point(lod.center);
+        let distance    = cameras.iter().map(|camera| camera.distance(center)).fold(f32::MAX, f32::min);
+        let level       = lod.level_for_distance(distance);
+
+        if lod.active != Some(level) {
+            lod.active  = Some(level);
+            handle.0    = lod.levels[level].handle.clone();
+        }
+    }
+}
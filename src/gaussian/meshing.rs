@@ -0,0 +1,367 @@
+// Gaussian cloud to mesh. Splats are summed into a density grid and its iso-surface is
+// polygonized, giving an approximate surface for collision or export.
+
+use std::collections::HashMap;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+use bevy_gaussian_splatting::Gaussian3d;
+
+use crate::gaussian::cpu_transform::unit_rotation;
+
+/// Splats contribute density out to this many standard deviations.
+const CUTOFF_SIGMAS: f32 = 3.0;
+
+/// Corner offsets of a grid cell, indexed by `x + 2y + 4z`.
+const CELL_CORNERS: [UVec3; 8] = [
+    UVec3::new(0, 0, 0),
+    UVec3::new(1, 0, 0),
+    UVec3::new(0, 1, 0),
+    UVec3::new(1, 1, 0),
+    UVec3::new(0, 0, 1),
+    UVec3::new(1, 0, 1),
+    UVec3::new(0, 1, 1),
+    UVec3::new(1, 1, 1),
+];
+
+/// The six tetrahedra around the cell diagonal from corner 0 to corner 7, one per axis order.
+/// Neighboring cells split their shared faces the same way, so the surface has no cracks.
+const CELL_TETRAHEDRA: [[usize; 4]; 6] = [
+    [0, 1, 3, 7],
+    [0, 1, 5, 7],
+    [0, 2, 3, 7],
+    [0, 2, 6, 7],
+    [0, 4, 5, 7],
+    [0, 4, 6, 7],
+];
+
+
+
+
+
+
+
+/// Extract an approximate surface mesh from a cloud.
+///
+/// Each visible splat adds `opacity * exp(-0.5 * d²)` to a density grid, where `d` is the
+/// distance from its center in units of its scale. Splat axes are widened to at least half a
+/// grid cell so flat surfels still land on the grid. `resolution` is the number of cells along
+/// the longest side of the cloud's bounds; the other sides use the same cell size. The surface
+/// where density equals `iso` is polygonized with marching tetrahedra (every cell split into
+/// six), with vertices shared between neighboring triangles and smooth normals.
+///
+/// Returns an empty mesh when the cloud has no visible splats, or when the grid would have more
+/// samples than fit in memory.
+pub fn cloud_to_mesh(
+    gaussians:  &[Gaussian3d],
+    resolution: u32,
+    iso:        f32,
+) -> Mesh {
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+
+    let grid = DensityGrid::accumulate(gaussians, resolution.max(1));
+    let (positions, indices) = match &grid {
+        Some(grid) => polygonize(grid, iso),
+        None       => (Vec::new(), Vec::new()),
+    };
+
+    let has_faces = !indices.is_empty();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_indices(Indices::U32(indices));
+
+    if has_faces {
+        mesh.compute_smooth_normals();
+    } else {
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, Vec::<[f32; 3]>::new());
+    }
+
+    mesh
+}
+
+
+
+
+
+
+
+// Density samples at the corners of a regular grid of cells.
+struct DensityGrid {
+    origin:     Vec3,
+    cell:       f32,
+    /// Number of samples along each axis, one more than the number of cells
+    dims:       UVec3,
+    values:     Vec<f32>,
+}
+
+impl DensityGrid {
+
+    fn accumulate(
+        gaussians:  &[Gaussian3d],
+        resolution: u32,
+    ) -> Option<Self> {
+
+        let visible: Vec<&Gaussian3d> = gaussians
+            .iter()
+            .filter(|g| g.position_visibility.visibility > 0.0 && g.scale_opacity.opacity > 0.0)
+            .collect();
+
+        if visible.is_empty() {
+            return None;
+        }
+
+        // Bounds of every splat's cutoff box
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for g in &visible {
+            let center  = Vec3::from(g.position_visibility.position);
+            let reach   = Vec3::from(g.scale_opacity.scale).abs().max_element() * CUTOFF_SIGMAS;
+            min = min.min(center - reach);
+            max = max.max(center + reach);
+        }
+
+        let cell = ((max - min).max_element() / resolution as f32).max(f32::EPSILON);
+
+        // One cell of padding on every side keeps the surface closed at the bounds
+        let origin  = min - cell;
+        let dims    = ((max - min) / cell).ceil().as_uvec3() + UVec3::splat(3);
+
+        // In usize: the product overflows u32 at high resolutions
+        let len = (dims.x as usize)
+            .checked_mul(dims.y as usize)?
+            .checked_mul(dims.z as usize)?;
+
+        // A failed allocation would abort rather than return
+        let mut values = Vec::new();
+        values.try_reserve_exact(len).ok()?;
+        values.resize(len, 0.0);
+
+        let mut grid = Self {
+            origin,
+            cell,
+            dims,
+            values,
+        };
+
+        for g in visible {
+            grid.splat(g);
+        }
+
+        Some(grid)
+    }
+
+
+
+    fn splat(
+        &mut self,
+        g: &Gaussian3d,
+    ) {
+
+        let center      = Vec3::from(g.position_visibility.position);
+        let scale       = Vec3::from(g.scale_opacity.scale).abs().max(Vec3::splat(self.cell * 0.5));
+        let inverse     = unit_rotation(g).inverse();
+        let opacity     = g.scale_opacity.opacity;
+        let reach       = scale.max_element() * CUTOFF_SIGMAS;
+
+        let lo = ((center - reach - self.origin) / self.cell).floor().max(Vec3::ZERO).as_uvec3();
+        let hi = ((center + reach - self.origin) / self.cell).ceil().as_uvec3().min(self.dims - 1);
+
+        for z in lo.z..=hi.z {
+            for y in lo.y..=hi.y {
+                for x in lo.x..=hi.x {
+                    let sample      = UVec3::new(x, y, z);
+                    let local       = inverse * (self.position(sample) - center) / scale;
+                    let distance_sq = local.length_squared();
+
+                    if distance_sq <= CUTOFF_SIGMAS * CUTOFF_SIGMAS {
+                        let index = self.index(sample);
+                        self.values[index] += opacity * (-0.5 * distance_sq).exp();
+                    }
+                }
+            }
+        }
+    }
+
+
+
+    fn index(
+        &self,
+        sample: UVec3,
+    ) -> usize {
+
+        let (x, y, z)           = (sample.x as usize, sample.y as usize, sample.z as usize);
+        let (width, height)     = (self.dims.x as usize, self.dims.y as usize);
+
+        x + width * (y + height * z)
+    }
+
+
+
+    fn position(
+        &self,
+        sample: UVec3,
+    ) -> Vec3 {
+
+        self.origin + sample.as_vec3() * self.cell
+    }
+}
+
+
+
+// Marching tetrahedra over every grid cell. Triangles face away from the dense side.
+fn polygonize(
+    grid:   &DensityGrid,
+    iso:    f32,
+) -> (Vec<[f32; 3]>, Vec<u32>) {
+
+    let mut positions   = Vec::new();
+    let mut indices     = Vec::new();
+
+    // Vertices are keyed by the grid edge they lie on, so neighboring triangles share them
+    let mut edge_vertices: HashMap<(usize, usize), u32> = HashMap::new();
+
+    let cells = grid.dims - 1;
+
+    for z in 0..cells.z {
+        for y in 0..cells.y {
+            for x in 0..cells.x {
+                let base    = UVec3::new(x, y, z);
+                let corners = CELL_CORNERS.map(|offset| grid.index(base + offset));
+
+                let inside_count = corners.iter().filter(|&&c| grid.values[c] >= iso).count();
+                if inside_count == 0 || inside_count == 8 {
+                    continue;
+                }
+
+                for tet in CELL_TETRAHEDRA {
+                    let tet_corners = tet.map(|i| (corners[i], base + CELL_CORNERS[i]));
+
+                    let (inside, outside): (Vec<_>, Vec<_>) = tet_corners
+                        .into_iter()
+                        .partition(|(index, _)| grid.values[*index] >= iso);
+
+                    // Corners in pairs of (inside, outside) for every crossing edge, grouped into
+                    // one triangle (1 or 3 inside) or a quad (2 inside)
+                    let crossings: Vec<_> = match (inside.len(), outside.len()) {
+                        (1, 3) => outside.iter().map(|o| (inside[0], *o)).collect(),
+                        (3, 1) => inside.iter().map(|i| (*i, outside[0])).collect(),
+                        (2, 2) => vec![
+                            (inside[0], outside[0]),
+                            (inside[0], outside[1]),
+                            (inside[1], outside[1]),
+                            (inside[1], outside[0]),
+                        ],
+                        _ => continue,
+                    };
+
+                    let mut vertex = |a: &(usize, UVec3), b: &(usize, UVec3)| -> u32 {
+                        let key = (a.0.min(b.0), a.0.max(b.0));
+                        *edge_vertices.entry(key).or_insert_with(|| {
+                            let (da, db)    = (grid.values[a.0], grid.values[b.0]);
+                            let t           = if da != db { ((iso - da) / (db - da)).clamp(0.0, 1.0) } else { 0.5 };
+                            let p           = grid.position(a.1).lerp(grid.position(b.1), t);
+
+                            positions.push(p.to_array());
+                            (positions.len() - 1) as u32
+                        })
+                    };
+
+                    let ring: Vec<u32> = crossings.iter().map(|(a, b)| vertex(a, b)).collect();
+
+                    // Outward is from the inside corners towards the outside ones
+                    let centroid = |corners: &[(usize, UVec3)]| {
+                        corners.iter().map(|c| grid.position(c.1)).sum::<Vec3>() / corners.len() as f32
+                    };
+                    let outward = centroid(&outside) - centroid(&inside);
+
+                    let triangles: &[[usize; 3]] = if ring.len() == 3 { &[[0, 1, 2]] } else { &[[0, 1, 2], [0, 2, 3]] };
+
+                    for &[i, j, k] in triangles {
+                        let [a, b, c] = [ring[i], ring[j], ring[k]].map(|v| Vec3::from(positions[v as usize]));
+
+                        if (b - a).cross(c - a).dot(outward) >= 0.0 {
+                            indices.extend([ring[i], ring[j], ring[k]]);
+                        } else {
+                            indices.extend([ring[i], ring[k], ring[j]]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    (positions, indices)
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gaussian::{
+        cpu_mesh_to_gaussians::points_to_gaussians,
+        settings::PointCloudSettings,
+        shapes::fibonacci_sphere,
+    };
+
+    #[test]
+    fn sphere_cloud_gives_a_closed_outward_mesh() {
+        let settings    = PointCloudSettings { scale: 0.08, ..default() };
+        let gaussians   = points_to_gaussians(&fibonacci_sphere(4000), None, Transform::IDENTITY, &settings);
+        let mesh        = cloud_to_mesh(&gaussians, 32, 0.5);
+
+        let positions   = mesh.attribute(Mesh::ATTRIBUTE_POSITION).unwrap().as_float3().unwrap();
+        let normals     = mesh.attribute(Mesh::ATTRIBUTE_NORMAL).unwrap().as_float3().unwrap();
+        assert!(positions.len() > 100, "{}", positions.len());
+
+        // A shell around the sphere: its outer side faces out, its inner side in
+        let facing_away = positions
+            .iter()
+            .zip(normals)
+            .filter(|&(p, n)| {
+                let (p, n) = (Vec3::from(*p), Vec3::from(*n));
+                (p.length() > 1.0) == (n.dot(p) > 0.0)
+            })
+            .count();
+        assert!(facing_away as f32 > 0.95 * positions.len() as f32, "{facing_away}/{}", positions.len());
+
+        // Closed: every edge is shared by exactly two triangles
+        let indices: Vec<usize> = mesh.indices().unwrap().iter().collect();
+        let mut edges = HashMap::new();
+        for tri in indices.chunks_exact(3) {
+            for k in 0..3 {
+                let (a, b) = (tri[k], tri[(k + 1) % 3]);
+                *edges.entry((a.min(b), a.max(b))).or_insert(0) += 1;
+            }
+        }
+        assert!(edges.values().all(|&count| count == 2));
+    }
+
+    #[test]
+    fn empty_cloud_gives_an_empty_mesh() {
+        assert_eq!(cloud_to_mesh(&[], 8, 0.5).count_vertices(), 0);
+    }
+
+    #[test]
+    fn oversized_grid_gives_an_empty_mesh() {
+        let gaussians = points_to_gaussians(&[Vec3::ZERO], None, Transform::IDENTITY, &default());
+
+        // About 2^60 samples: fits in usize, but not in memory
+        assert_eq!(cloud_to_mesh(&gaussians, 1 << 20, 0.5).count_vertices(), 0);
+        assert_eq!(cloud_to_mesh(&gaussians, u32::MAX, 0.5).count_vertices(), 0);
+    }
+
+    #[test]
+    fn grid_indices_past_u32() {
+        let grid = DensityGrid { origin: Vec3::ZERO, cell: 1.0, dims: UVec3::new(70_000, 70_000, 3), values: Vec::new() };
+        assert_eq!(grid.index(UVec3::new(1, 0, 2)), 1 + 2 * 70_000 * 70_000);
+    }
+}
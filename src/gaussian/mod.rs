@@ -1,20 +1,47 @@
 // Gaussian module - pure functions for creating and manipulating Gaussian clouds
 
 pub mod mass;
+pub mod builder;
+pub mod color;
 pub mod cpu_mesh_to_gaussians;
+pub mod cpu_transform;
 pub mod gpu_mesh_to_gaussians;
+pub mod io;
+pub mod lod;
+pub mod meshing;
+pub mod noise;
+pub mod rng;
 pub mod settings;
+pub mod shapes;
+pub mod spatial;
+pub mod stats;
+#[cfg(feature = "sh_rotated_interpolation")]
+pub mod sh_rotation;
+pub mod spawn;
 
 
 // Re-export the main public API
 pub use mass::*;
+pub use builder::*;
+pub use color::*;
 pub use cpu_mesh_to_gaussians::*;
+pub use cpu_transform::*;
 pub use gpu_mesh_to_gaussians::*;
+pub use io::*;
+pub use lod::*;
+pub use meshing::*;
 pub use settings::*;
+pub use shapes::*;
+pub use spatial::*;
+pub use stats::*;
+pub use spawn::*;
+
+use std::collections::HashMap;
 
 use bevy::{
     prelude::{Mesh3d, *},
-    render::mesh::{Indices, VertexAttributeValues},
+    render::mesh::{Indices, PrimitiveTopology},
+    tasks::{futures::check_ready, AsyncComputeTaskPool, Task},
 };
 
 use bevy_gaussian_splatting::{
@@ -23,6 +50,11 @@ use bevy_gaussian_splatting::{
     sort::SortMode,
 };
 
+use crate::debug::{
+    hot_trace,
+    metrics::{count_gaussians, update_metrics, GaussianMetrics},
+};
+
 
 
 
@@ -31,28 +63,170 @@ use bevy_gaussian_splatting::{
 
 /// Component to mark and configure mesh to Gaussian conversion.
 #[derive(Component, Debug, Clone, Reflect)]
+#[reflect(Component, Default)]
 pub struct MeshToGaussian {
-    pub mode:               MeshToGaussianMode,
-    pub surfel_thickness:   f32,
-    pub hide_source_mesh:   bool,
-    pub realtime:           bool,
+    pub mode:                           MeshToGaussianMode,
+    pub backend:                        MeshToGaussianBackend,
+    pub surfel_thickness:               f32,
+    pub hide_source_mesh:               bool,
+    pub realtime:                       bool,
+    pub mesh_selection:                 MeshSelection,
+    /// Depth sort for the spawned clouds. `None` uses `Radix` on the GPU backend and `Rayon` on
+    /// the CPU backend.
+    pub sort_mode:                      Option<SortMode>,
+    /// Requested interval between sorts, added to the clouds as a [`CloudSortPeriod`].
+    pub sort_period_ms:                 Option<usize>,
+    /// Passed to the clouds' `CloudSettings`. Turning it off stops faint splats from growing
+    /// and smearing.
+    pub opacity_adaptive_radius:        bool,
+    /// GPU backend only: reveal the clouds this many splats per second instead of all at once,
+    /// through a [`CloudReveal`](gpu_mesh_to_gaussians::CloudReveal). 0 shows everything
+    /// immediately.
+    pub reveal_rate:                    f32,
+    /// Once converted, draw the source meshes' triangles as a gizmo wireframe over the cloud,
+    /// to check splat alignment. Independent of `hide_source_mesh`; needs Bevy's gizmo plugin.
+    pub debug_show_source_wireframe:    bool,
 }
 
 impl Default for MeshToGaussian {
     fn default() -> Self {
         Self {
-            mode:               MeshToGaussianMode::TrianglesOneToOne,
-            surfel_thickness:   0.01,
-            hide_source_mesh:   true,
-            realtime:           false,
+            mode:                           MeshToGaussianMode::TrianglesOneToOne,
+            backend:                        MeshToGaussianBackend::Gpu,
+            surfel_thickness:               0.01,
+            hide_source_mesh:               true,
+            realtime:                       false,
+            mesh_selection:                 MeshSelection::FirstFound,
+            sort_mode:                      None,
+            sort_period_ms:                 None,
+            opacity_adaptive_radius:        true,
+            reveal_rate:                    0.0,
+            debug_show_source_wireframe:    false,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+impl MeshToGaussian {
+    /// Fluent alternative to a struct literal, starting from the defaults.
+    pub fn builder() -> MeshToGaussianBuilder {
+        MeshToGaussianBuilder::default()
+    }
+
+    /// The CPU conversion settings this component converts with: its mode, with
+    /// `surfel_thickness` as the face splat scale and defaults for everything else.
+    pub fn conversion_settings(&self) -> MeshConversionSettings {
+        MeshConversionSettings {
+            mode:       self.mode,
+            face_scale: self.surfel_thickness,
+            ..default()
+        }
+    }
+}
+
+/// Builds a [`MeshToGaussian`] one setting at a time; see the fields there for what each does.
+#[derive(Debug, Clone, Default)]
+pub struct MeshToGaussianBuilder {
+    config: MeshToGaussian,
+}
+
+impl MeshToGaussianBuilder {
+
+    pub fn mode(mut self, mode: MeshToGaussianMode) -> Self {
+        self.config.mode = mode;
+        self
+    }
+
+    pub fn backend(mut self, backend: MeshToGaussianBackend) -> Self {
+        self.config.backend = backend;
+        self
+    }
+
+    pub fn surfel_thickness(mut self, thickness: f32) -> Self {
+        self.config.surfel_thickness = thickness;
+        self
+    }
+
+    pub fn hide_source_mesh(mut self, hide: bool) -> Self {
+        self.config.hide_source_mesh = hide;
+        self
+    }
+
+    pub fn realtime(mut self, realtime: bool) -> Self {
+        self.config.realtime = realtime;
+        self
+    }
+
+    pub fn mesh_selection(mut self, selection: MeshSelection) -> Self {
+        self.config.mesh_selection = selection;
+        self
+    }
+
+    pub fn sort_mode(mut self, sort_mode: SortMode) -> Self {
+        self.config.sort_mode = Some(sort_mode);
+        self
+    }
+
+    pub fn sort_period_ms(mut self, period_ms: usize) -> Self {
+        self.config.sort_period_ms = Some(period_ms);
+        self
+    }
+
+    pub fn opacity_adaptive_radius(mut self, enabled: bool) -> Self {
+        self.config.opacity_adaptive_radius = enabled;
+        self
+    }
+
+    pub fn reveal_rate(mut self, splats_per_second: f32) -> Self {
+        self.config.reveal_rate = splats_per_second;
+        self
+    }
+
+    pub fn debug_show_source_wireframe(mut self, show: bool) -> Self {
+        self.config.debug_show_source_wireframe = show;
+        self
+    }
+
+    pub fn build(self) -> MeshToGaussian {
+        self.config
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Reflect)]
 pub enum MeshToGaussianMode {
     /// Generates one gaussian splat for each triangle in the mesh.
     TrianglesOneToOne,
+    /// Merges coplanar triangle pairs sharing a diagonal into one rectangular splat per quad.
+    /// Unpaired triangles fall back to one splat each. CPU conversion only.
+    Quads,
+    /// Splits each triangle into 4^`levels` sub-triangles and emits one splat per sub-triangle,
+    /// colored from interpolated vertex normals. `levels = 0` is identical to `TrianglesOneToOne`.
+    /// Levels above 6 are clamped. CPU conversion only.
+    SubdividedTriangles { levels: u8 },
+    /// Emits `layers` copies of each triangle surfel, the k-th moved `k * offset` along the face
+    /// normal, so closed meshes read as solid instead of hollow. A negative `offset` grows the
+    /// shell inward. `layers = 0` is treated as 1.
+    ThickShell { layers: u8, offset: f32 },
+}
+
+/// Which meshes under a `MeshToGaussian` entity get converted.
+#[derive(Debug, Clone, PartialEq, Eq, Reflect)]
+pub enum MeshSelection {
+    /// The first mesh found in a depth-first walk of the hierarchy.
+    FirstFound,
+    /// Every mesh in the hierarchy.
+    All,
+    /// Every mesh whose entity, or one of its ancestors, has this `Name`.
+    ByName(String),
+}
+
+/// Where the mesh to gaussian conversion runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum MeshToGaussianBackend {
+    /// Allocates an empty cloud and fills it with the `tri_to_splat` compute shader.
+    Gpu,
+    /// Runs `mesh_to_gaussians` on the loaded mesh in the background and spawns a fully
+    /// populated cloud once it finishes.
+    Cpu,
 }
 
 
@@ -73,10 +247,37 @@ impl Plugin for GenGaussianGpuPlugin {
             Update,
             (
                 process_new_meshes_for_gpu_conversion,
+                poll_cpu_conversion_tasks,
+                process_mesh_groups,
                 update_tri_to_splat_params,
+                sync_source_mesh_visibility,
+                apply_global_cloud_settings,
+                apply_cloud_sort_periods,
                 debug_entities,
+                log_new_cloud_stats,
             ),
         );
+        app.add_event::<MeshConverted>();
+        app.init_resource::<GaussianMetrics>();
+        app.init_resource::<DebugEntitiesInterval>();
+        app.init_resource::<GaussianGlobalSettings>();
+        app.init_resource::<LogCloudStats>();
+        app.add_systems(Update, (update_metrics, count_gaussians));
+        app.add_systems(Update, draw_source_wireframes.run_if(resource_exists::<GizmoConfigStore>));
+        app.add_systems(PostUpdate, lod::select_cloud_lods.after(TransformSystem::TransformPropagate));
+        app.register_type::<MeshConversionSettings>();
+        app.register_type::<PointCloudSettings>();
+        app.register_type::<ColorMode>();
+        app.register_type::<GaussianGlobalSettings>();
+        app.register_type::<MeshToGaussian>();
+        app.register_type::<MeshToGaussianMode>();
+        app.register_type::<MeshToGaussianBackend>();
+        app.register_type::<MeshSelection>();
+        app.register_type::<MeshToGaussianGroup>();
+        app.register_type::<CloudSortPeriod>();
+        app.register_type::<CloudBaseSettings>();
+        app.register_type::<CloudLod>();
+        app.register_type::<crate::sdf_module::SDFConversionSettings>();
         app.add_plugins(TriToSplatPlugin);
     }
 }
@@ -87,9 +288,24 @@ impl Plugin for GenGaussianGpuPlugin {
 
 
 
-/// Backreference from a source entity to the spawned cloud asset handle.
+/// Backreference from a source entity to the spawned cloud asset handles: one per converted
+/// mesh on the GPU path, a single merged cloud on the CPU path.
 #[derive(Component, Clone)]
-pub struct MeshToGaussianCloud(pub Handle<bevy_gaussian_splatting::PlanarGaussian3d>);
+pub struct MeshToGaussianCloud {
+    pub handles:        Vec<Handle<bevy_gaussian_splatting::PlanarGaussian3d>>,
+    /// Total number of splats across the clouds
+    pub splat_count:    u32,
+}
+
+
+/// Sent when a source entity's cloud has been spawned. For the GPU backend the splats are
+/// filled in by the compute pass shortly after.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MeshConverted {
+    pub source:         Entity,
+    pub cloud:          Entity,
+    pub splat_count:    u32,
+}
 
 
 /// Marker to prevent reprocessing a mesh every frame if `realtime` is false.
@@ -97,157 +313,528 @@ pub struct MeshToGaussianCloud(pub Handle<bevy_gaussian_splatting::PlanarGaussia
 pub struct ConvertedOnce;
 
 
+/// Batches a `MeshToGaussian` source with every other source carrying the same id. Once all
+/// of their meshes have loaded they are converted on the CPU, moved into world space and merged
+/// into one cloud, so the whole group shares a single sort and draw. The sources' `backend`
+/// is ignored. Sources added to a group after it was merged form a new cloud.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[reflect(Component)]
+pub struct MeshToGaussianGroup(pub u32);
+
+
+/// Requested interval between depth sorts for a cloud, in milliseconds.
+///
+/// The renderer sorts every cloud on one global `SortConfig` period, so this is a request rather
+/// than a per-cloud schedule: while any cloud carries one, `SortConfig::period_ms` is set to the
+/// shortest requested period, and the previous period comes back when the last one is removed.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub struct CloudSortPeriod(pub usize);
+
+
+/// A cloud's own `global_scale` and `global_opacity`, which `GaussianGlobalSettings` multiplies
+/// into its `CloudSettings`.
+///
+/// Captured from `CloudSettings` when the cloud is spawned (or its settings are replaced); edit
+/// this rather than `CloudSettings` to change the cloud's own values afterwards.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Reflect)]
+#[reflect(Component)]
+pub struct CloudBaseSettings {
+    pub global_scale:   f32,
+    pub global_opacity: f32,
+}
+
+
 /// Component on the cloud entity that links it back to its source entity.
 #[derive(Component, Clone, Copy, Debug)]
 pub struct CloudOf(pub Entity);
 
 
+/// In-flight CPU conversion for a source entity. The source stays visible until the task
+/// finishes and its cloud is spawned.
+#[derive(Component)]
+pub struct ConvertingTask {
+    pub task:           Task<bevy_gaussian_splatting::PlanarGaussian3d>,
+    /// Transform for the spawned cloud: the mesh's global transform when a single mesh is
+    /// converted in local space, identity when several are merged in world space
+    pub mesh_transform: GlobalTransform,
+}
+
 
 
 
 
 
-/// Finds entities with `MeshToGaussian`, waits for their mesh to load, then creates a correctly
-/// sized and positioned Gaussian cloud.
+
+/// Finds entities with `MeshToGaussian`, waits for their selected meshes to load, then creates
+/// correctly sized and positioned Gaussian clouds.
 fn process_new_meshes_for_gpu_conversion(
     mut commands:       Commands,
     mut clouds:         ResMut<Assets<bevy_gaussian_splatting::PlanarGaussian3d>>,
     meshes:             Res<Assets<Mesh>>,
     mut visibility_q:   Query<&mut Visibility>,
-    source_q:           Query<(Entity, &MeshToGaussian), Without<ConvertedOnce>>,
+    mut converted:      EventWriter<MeshConverted>,
+    source_q:           Query<
+        (Entity, &MeshToGaussian),
+        (Without<ConvertedOnce>, Without<ConvertingTask>, Without<MeshToGaussianGroup>),
+    >,
     children_q:         Query<&Children>,
     mesh_3d_q:          Query<&Mesh3d>,
+    name_q:             Query<&Name>,
     
     transform_q:        Query<&GlobalTransform> // Query for transforms to correctly position the cloud.
 ) {
 
 
-    // Helper now returns the mesh handle AND its global transform.
-    fn find_descendant_mesh_with_transform(
-        root:           Entity,
-        children_q:     &Query<&Children>,
-        mesh_3d_q:      &Query<&Mesh3d>,
-        transform_q:    &Query<&GlobalTransform>,
-    ) -> Option<(Handle<Mesh>, GlobalTransform)> {
-
-        let mut stack = vec![root];
-        
-        while let Some(entity) = stack.pop() {
-            if let Ok(mesh_3d) = mesh_3d_q.get(entity) {
-                if let Ok(transform) = transform_q.get(entity) {
-                    return Some((mesh_3d.0.clone(), *transform));
-                }
-            }
-            if let Ok(children) = children_q.get(entity) {
-                stack.extend(children.iter());
-            }
-        }
-        None
-    }
-
-
-
     for (source_entity, config) in &source_q {
 
-        // Find the mesh and its transform.
-        let Some((mesh_handle, mesh_transform)) = find_descendant_mesh_with_transform(
+        // Find the selected meshes and their transforms.
+        let found = find_selected_meshes(
             source_entity,
+            &config.mesh_selection,
             &children_q,
             &mesh_3d_q,
-            &transform_q
-        ) else {
+            &transform_q,
+            &name_q,
+        );
+
+        if found.is_empty() {
+            continue;
+        }
+
+        // Wait until every selected mesh has loaded
+        let Some(selected) = found
+            .iter()
+            .map(|(handle, transform)| meshes.get(handle).map(|mesh| (mesh, *transform)))
+            .collect::<Option<Vec<_>>>()
+        else {
             continue;
         };
 
-        let Some(mesh) = meshes.get(&mesh_handle) else {
+        if config.backend == MeshToGaussianBackend::Cpu {
+
+            let task_config = config.clone();
+
+            // A single mesh converts in local space and the cloud entity carries its transform;
+            // several are moved into world space and merged into one cloud. The conversion runs
+            // on the async compute pool and `poll_cpu_conversion_tasks` spawns the cloud when done.
+            let task_meshes: Vec<(Mesh, GlobalTransform)> = selected
+                .iter()
+                .map(|(mesh, transform)| ((*mesh).clone(), *transform))
+                .collect();
+
+            let mesh_transform = match task_meshes.as_slice() {
+                [(_, transform)]    => *transform,
+                _                   => GlobalTransform::IDENTITY,
+            };
+
+            let task = AsyncComputeTaskPool::get().spawn(async move {
+                if let [(mesh, _)] = task_meshes.as_slice() {
+                    return convert_loaded_mesh(mesh, Transform::IDENTITY, &task_config);
+                }
+
+                let settings = task_config.conversion_settings();
+                let parts: Vec<Vec<bevy_gaussian_splatting::Gaussian3d>> = task_meshes
+                    .iter()
+                    .map(|(mesh, transform)| {
+                        let local = mesh_to_gaussians(mesh, Transform::IDENTITY, &settings);
+                        transform_cloud(&local, transform.compute_transform())
+                    })
+                    .collect();
+
+                to_planar(&combine_clouds(&parts.iter().map(Vec::as_slice).collect::<Vec<_>>()))
+            });
+
+            commands
+                .entity(source_entity)
+                .insert(ConvertingTask { task, mesh_transform });
+
             continue;
+        }
+
+        let (layers, shell_offset) = match config.mode {
+            MeshToGaussianMode::TrianglesOneToOne               => (1, 0.0),
+            MeshToGaussianMode::ThickShell { layers, offset }   => (u32::from(layers.max(1)), offset),
+            _ => {
+                warn!(
+                    "MeshToGaussian on {:?}: {:?} is not supported by the GPU path; using TrianglesOneToOne",
+                    source_entity, config.mode
+                );
+                (1, 0.0)
+            }
         };
 
-        let Some(VertexAttributeValues::Float32x3(pos)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else {
+        // The GPU path fills one cloud per mesh from that mesh's own input buffers
+        let mut spawned = Vec::with_capacity(selected.len());
+
+        for (mesh, mesh_transform) in selected {
+
+            let Some(corners) = cpu_mesh_to_gaussians::read_positions(mesh) else {
+                continue;
+            };
+
+            let positions: Vec<[f32; 4]> = corners
+                .iter()
+                .map(|p| p.extend(1.0).to_array())
+                .collect();
+
+            // Degenerate triangles would give the shader a zero normal to normalize
+            let indices: Vec<u32> = mesh_triangle_indices(mesh)
+                .chunks_exact(3)
+                .filter(|tri| {
+                    cpu_mesh_to_gaussians::triangle_corners(&corners, [tri[0], tri[1], tri[2]])
+                        .is_some_and(cpu_mesh_to_gaussians::is_valid_triangle)
+                })
+                .flatten()
+                .copied()
+                .collect();
+            let tri_count   = (indices.len() / 3) as u32;
+            if tri_count == 0 {
+                continue;
+            }
+
+            info!("Processing mesh for {:?}: found {} triangles.", source_entity, tri_count);
+
+            // One splat per triangle per shell layer
+            let splat_count = tri_count * layers;
+
+
+            let zero_pv     = PositionVisibility            { position:     [0.0; 3], visibility: 0.0 };
+            let zero_sh     = SphericalHarmonicCoefficients { coefficients: [0.0; 48] };
+            let zero_rot    = Rotation                      { rotation:     [0.0; 4] };
+            let zero_so     = ScaleOpacity                  { scale:        [0.0; 3], opacity: 0.0 };
+
+            let cloud_asset = bevy_gaussian_splatting::PlanarGaussian3d {
+                position_visibility:    vec![zero_pv;   splat_count as usize],
+                spherical_harmonic:     vec![zero_sh;   splat_count as usize],
+                rotation:               vec![zero_rot;  splat_count as usize],
+                scale_opacity:          vec![zero_so;   splat_count as usize],
+            };
+
+            let cloud_handle = clouds.add(cloud_asset);
+
+
+            // Spawn the cloud entity
+            let cloud_entity = commands.spawn((
+                bevy_gaussian_splatting::PlanarGaussian3dHandle(cloud_handle.clone()),
+                bevy_gaussian_splatting::CloudSettings {
+                    sort_mode: config.sort_mode.unwrap_or(SortMode::Radix),
+                    opacity_adaptive_radius: config.opacity_adaptive_radius,
+                    ..Default::default()
+                },
+                Name::new("GeneratedGaussianCloud"),
+                CloudOf(source_entity),
+                gpu_mesh_to_gaussians::TriToSplatCpuInput {
+                    positions,
+                    indices,
+                    tri_count,
+                    layers,
+                    shell_offset,
+                },
+                // Apply the captured transform of the original mesh.
+                mesh_transform,
+                Visibility::Visible,
+            )).id();
+
+            if let Some(period) = config.sort_period_ms {
+                commands.entity(cloud_entity).insert(CloudSortPeriod(period));
+            }
+
+            if config.reveal_rate > 0.0 {
+                commands.entity(cloud_entity).insert(gpu_mesh_to_gaussians::CloudReveal {
+                    rate:       config.reveal_rate,
+                    progress:   0.0,
+                });
+            }
+
+            spawned.push((cloud_entity, cloud_handle, splat_count));
+        }
+
+        if spawned.is_empty() {
             // TODO: Use change detection instead
-             if !config.realtime { 
+            if !config.realtime {
                 commands
                     .entity(source_entity)
                     .insert(ConvertedOnce);
-                }
+            }
+            continue;
+        }
 
-             continue;
-        };
 
-        let positions: Vec<[f32; 4]> = pos
-            .iter()
-            .map(|p| [p[0], p[1], p[2], 1.0])
-            .collect();
+        finish_source(
+            &mut commands,
+            &mut visibility_q,
+            &mut converted,
+            source_entity,
+            config,
+            spawned,
+        );
+    }
+}
 
-        let indices: Vec<u32> = match mesh.indices() {
-            Some(Indices::U16(xs))  => xs.iter().map(|&i| i as u32).collect(),
-            Some(Indices::U32(xs))  => xs.clone(),
-            None                    => (0..positions.len() as u32).collect(),
-        };
 
 
-        let tri_count = (indices.len() / 3) as u32;
-        if tri_count == 0 {
-            if !config.realtime { commands.entity(source_entity).insert(ConvertedOnce); }
-            continue;
-        }
 
-        info!("Processing mesh for {:?}: found {} triangles.", source_entity, tri_count);
 
 
-        let zero_pv     = PositionVisibility            { position:     [0.0; 3], visibility: 0.0 };
-        let zero_sh     = SphericalHarmonicCoefficients { coefficients: [0.0; 48] };
-        let zero_rot    = Rotation                      { rotation:     [0.0; 4] };
-        let zero_so     = ScaleOpacity                  { scale:        [0.0; 3], opacity: 0.0 };
 
-        let cloud_asset = bevy_gaussian_splatting::PlanarGaussian3d {
-            position_visibility:    vec![zero_pv;   tri_count as usize],
-            spherical_harmonic:     vec![zero_sh;   tri_count as usize],
-            rotation:               vec![zero_rot;  tri_count as usize],
-            scale_opacity:          vec![zero_so;   tri_count as usize],
+/// Spawns the cloud for every CPU conversion task that has finished.
+fn poll_cpu_conversion_tasks(
+    mut commands:       Commands,
+    mut clouds:         ResMut<Assets<bevy_gaussian_splatting::PlanarGaussian3d>>,
+    mut visibility_q:   Query<&mut Visibility>,
+    mut converted:      EventWriter<MeshConverted>,
+    mut task_q:         Query<(Entity, &MeshToGaussian, &mut ConvertingTask)>,
+) {
+
+    for (source_entity, config, mut converting) in &mut task_q {
+
+        let Some(cloud_asset) = check_ready(&mut converting.task) else {
+            continue;
         };
 
-        let cloud_handle = clouds.add(cloud_asset);
+        let splat_count = cloud_asset.position_visibility.len() as u32;
 
+        info!("Converted mesh for {:?} on the CPU: {} gaussians.", source_entity, splat_count);
+
+        let cloud_handle = clouds.add(cloud_asset);
 
-        // Spawn the cloud entity
-        commands.spawn((
+        let cloud_entity = commands.spawn((
             bevy_gaussian_splatting::PlanarGaussian3dHandle(cloud_handle.clone()),
             bevy_gaussian_splatting::CloudSettings {
-                sort_mode: SortMode::Radix,
+                sort_mode: config.sort_mode.unwrap_or(SortMode::Rayon),
+                opacity_adaptive_radius: config.opacity_adaptive_radius,
                 ..Default::default()
             },
             Name::new("GeneratedGaussianCloud"),
             CloudOf(source_entity),
-            gpu_mesh_to_gaussians::TriToSplatCpuInput {
-                positions,
-                indices,
-                tri_count,
+            converting.mesh_transform,
+            Visibility::Visible,
+        )).id();
+
+        if let Some(period) = config.sort_period_ms {
+            commands.entity(cloud_entity).insert(CloudSortPeriod(period));
+        }
+
+        commands.entity(source_entity).remove::<ConvertingTask>();
+
+        finish_source(
+            &mut commands,
+            &mut visibility_q,
+            &mut converted,
+            source_entity,
+            config,
+            vec![(cloud_entity, cloud_handle, splat_count)],
+        );
+    }
+}
+
+
+
+/// Merges every `MeshToGaussianGroup` whose sources' meshes have all loaded into one cloud.
+fn process_mesh_groups(
+    mut commands:       Commands,
+    mut clouds:         ResMut<Assets<bevy_gaussian_splatting::PlanarGaussian3d>>,
+    meshes:             Res<Assets<Mesh>>,
+    mut visibility_q:   Query<&mut Visibility>,
+    mut converted:      EventWriter<MeshConverted>,
+    source_q:           Query<(Entity, &MeshToGaussian, &MeshToGaussianGroup), Without<ConvertedOnce>>,
+    children_q:         Query<&Children>,
+    mesh_3d_q:          Query<&Mesh3d>,
+    name_q:             Query<&Name>,
+    transform_q:        Query<&GlobalTransform>,
+) {
+
+    let mut groups: HashMap<MeshToGaussianGroup, Vec<(Entity, &MeshToGaussian)>> = HashMap::new();
+
+    for (source_entity, config, group) in &source_q {
+        groups.entry(*group).or_default().push((source_entity, config));
+    }
+
+    'groups: for (group, sources) in groups {
+
+        // Wait until every member's selected meshes are available
+        let mut resolved = Vec::with_capacity(sources.len());
+
+        for &(source_entity, config) in &sources {
+            let found = find_selected_meshes(
+                source_entity,
+                &config.mesh_selection,
+                &children_q,
+                &mesh_3d_q,
+                &transform_q,
+                &name_q,
+            );
+
+            if found.is_empty() {
+                continue 'groups;
+            }
+
+            let Some(selected) = found
+                .iter()
+                .map(|(handle, transform)| meshes.get(handle).map(|mesh| (mesh, *transform)))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue 'groups;
+            };
+
+            resolved.push(selected);
+        }
+
+        let parts: Vec<Vec<bevy_gaussian_splatting::Gaussian3d>> = sources
+            .iter()
+            .zip(&resolved)
+            .map(|((_, config), selected)| {
+                let settings = config.conversion_settings();
+
+                selected
+                    .iter()
+                    .flat_map(|(mesh, mesh_transform)| {
+                        let local = mesh_to_gaussians(mesh, Transform::IDENTITY, &settings);
+                        transform_cloud(&local, mesh_transform.compute_transform())
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let merged = combine_clouds(&parts.iter().map(Vec::as_slice).collect::<Vec<_>>());
+
+        info!(
+            "Merged {} sources of {:?} into one cloud: {} gaussians.",
+            sources.len(),
+            group,
+            merged.len()
+        );
+
+        let cloud_handle = clouds.add(to_planar(&merged));
+
+        // Cloud settings come from the first member; the shortest requested sort period wins
+        let first       = sources[0].1;
+        let sort_mode   = first.sort_mode.unwrap_or(SortMode::Rayon);
+        let sort_period = sources.iter().filter_map(|(_, config)| config.sort_period_ms).min();
+
+        // Splats are already in world space
+        let cloud_entity = commands.spawn((
+            bevy_gaussian_splatting::PlanarGaussian3dHandle(cloud_handle.clone()),
+            bevy_gaussian_splatting::CloudSettings {
+                sort_mode,
+                opacity_adaptive_radius: first.opacity_adaptive_radius,
+                ..Default::default()
             },
-            // Apply the captured transform of the original mesh.
-            mesh_transform,
+            Name::new("GeneratedGaussianGroupCloud"),
+            Transform::IDENTITY,
             Visibility::Visible,
-        ));
+        )).id();
 
+        if let Some(period) = sort_period {
+            commands.entity(cloud_entity).insert(CloudSortPeriod(period));
+        }
 
-        if config.hide_source_mesh {
-            if let Ok(mut visibility) = visibility_q.get_mut(source_entity) {
-                *visibility = Visibility::Hidden;
-                info!("Hid source mesh entity {:?}", source_entity);
+        for (&(source_entity, config), part) in sources.iter().zip(&parts) {
+            finish_source(
+                &mut commands,
+                &mut visibility_q,
+                &mut converted,
+                source_entity,
+                config,
+                vec![(cloud_entity, cloud_handle.clone(), part.len() as u32)],
+            );
+        }
+    }
+}
+
+
+
+/// Meshes under `root` (depth first, including itself) picked by `selection`, with their global
+/// transforms.
+fn find_selected_meshes(
+    root:           Entity,
+    selection:      &MeshSelection,
+    children_q:     &Query<&Children>,
+    mesh_3d_q:      &Query<&Mesh3d>,
+    transform_q:    &Query<&GlobalTransform>,
+    name_q:         &Query<&Name>,
+) -> Vec<(Handle<Mesh>, GlobalTransform)> {
+
+    let mut found = Vec::new();
+
+    // The flag records whether the entity or one of its ancestors carries the selected name
+    let mut stack = vec![(root, false)];
+    
+    while let Some((entity, named)) = stack.pop() {
+
+        let named = named || match selection {
+            MeshSelection::ByName(name) => name_q.get(entity).is_ok_and(|n| n.as_str() == name),
+            _                           => false,
+        };
+
+        let selected = !matches!(selection, MeshSelection::ByName(_)) || named;
+
+        if selected {
+            if let (Ok(mesh_3d), Ok(transform)) = (mesh_3d_q.get(entity), transform_q.get(entity)) {
+                found.push((mesh_3d.0.clone(), *transform));
+
+                if *selection == MeshSelection::FirstFound {
+                    break;
+                }
             }
         }
+        if let Ok(children) = children_q.get(entity) {
+            stack.extend(children.iter().map(|child| (child, named)));
+        }
+    }
 
-        // TODO: Somehow implement change detection instead
-        commands
-            .entity(source_entity)
-            .insert(MeshToGaussianCloud(cloud_handle));
+    found
+}
 
-        if !config.realtime {
-            commands
-                .entity(source_entity)
-                .insert(ConvertedOnce);
+
+
+/// Hides the source if requested, links it to its clouds and announces each conversion.
+/// `spawned` holds the cloud entity, asset handle and splat count of every cloud.
+fn finish_source(
+    commands:       &mut Commands,
+    visibility_q:   &mut Query<&mut Visibility>,
+    converted:      &mut EventWriter<MeshConverted>,
+    source_entity:  Entity,
+    config:         &MeshToGaussian,
+    spawned:        Vec<(Entity, Handle<bevy_gaussian_splatting::PlanarGaussian3d>, u32)>,
+) {
+
+    if config.hide_source_mesh {
+        if let Ok(mut visibility) = visibility_q.get_mut(source_entity) {
+            // Kept from the first conversion if the source is converted again
+            commands.entity(source_entity).insert_if_new(HiddenSourceVisibility(*visibility));
+            *visibility = Visibility::Hidden;
+            info!("Hid source mesh entity {:?}", source_entity);
         }
     }
+
+    let mut cloud = MeshToGaussianCloud {
+        handles:        Vec::with_capacity(spawned.len()),
+        splat_count:    0,
+    };
+
+    for (cloud_entity, handle, splat_count) in spawned {
+        converted.write(MeshConverted {
+            source:         source_entity,
+            cloud:          cloud_entity,
+            splat_count,
+        });
+
+        cloud.handles.push(handle);
+        cloud.splat_count += splat_count;
+    }
+
+    // TODO: Somehow implement change detection instead
+    commands
+        .entity(source_entity)
+        .insert(cloud);
+
+    if !config.realtime {
+        commands
+            .entity(source_entity)
+            .insert(ConvertedOnce);
+    }
 }
 
 
@@ -266,23 +853,24 @@ fn update_tri_to_splat_params(
 
     let input_count = q_cloud_inputs.iter().count();
 
-    bevy::log::info!("update_tri_to_splat_params: found {} cloud inputs", input_count);
+    hot_trace!("update_tri_to_splat_params: found {} cloud inputs", input_count);
     
     let mut max_gauss = 0u32;
 
     for input in &q_cloud_inputs {
-        max_gauss = max_gauss.max(input.tri_count);
+        max_gauss = max_gauss.max(input.splat_count());
     }
 
     if max_gauss == 0 { 
-        bevy::log::info!("update_tri_to_splat_params: no gaussians to process");
+        hot_trace!("update_tri_to_splat_params: no gaussians to process");
         // Still update time params so shader has a valid uniform if needed.
     }
 
     let camera_count = q_cameras.iter().count();
-    bevy::log::info!("update_tri_to_splat_params: updating {} cameras with max_gauss={}", camera_count, max_gauss);
+    hot_trace!("update_tri_to_splat_params: updating {} cameras with max_gauss={}", camera_count, max_gauss);
 
-    let elapsed = time.elapsed_secs();
+    // The morph runs for 3 seconds of every cycle, then holds
+    let (elapsed, duration) = gpu_mesh_to_gaussians::morph_timing(time.elapsed_secs_f64(), 3.0);
 
     // Compute an approximate bounding sphere from the first available input's bounds.
     // If none, fall back to origin and unit radius.
@@ -309,7 +897,7 @@ fn update_tri_to_splat_params(
         commands.entity(cam).insert(gpu_mesh_to_gaussians::TriToSplatParams {
             gaussian_count:   max_gauss,
             elapsed_seconds:  elapsed,
-            duration_seconds: 3.0,
+            duration_seconds: duration,
             _pad:             0.0,
             sphere_center,
             sphere_radius,
@@ -327,24 +915,346 @@ fn update_tri_to_splat_params(
 
 
 
+/// Convert a mesh you already hold into a cloud the way a CPU-backend `MeshToGaussian` would,
+/// synchronously and without spawning anything. `transform` places the splats, e.g. the mesh
+/// entity's `GlobalTransform` for a cloud in world space.
+///
+/// Only the conversion fields of `config` are used (see
+/// [`MeshToGaussian::conversion_settings`]); `backend` is ignored, since this always runs on the
+/// CPU.
+pub fn convert_loaded_mesh(
+    mesh:       &Mesh,
+    transform:  Transform,
+    config:     &MeshToGaussian,
+) -> bevy_gaussian_splatting::PlanarGaussian3d {
+
+    mesh_to_planar_gaussians(mesh, transform, &config.conversion_settings())
+}
+
+
+
+/// Triangle-list indices for a mesh as `u32`, widening `U16` indices and generating
+/// `0..vertex_count` for non-indexed meshes. Triangle strips are unrolled into a list.
+///
+/// The result always holds a whole number of triangles: a trailing partial triangle is
+/// dropped with a warning, so buffer sizing and per-triangle dispatch agree.
+pub fn mesh_triangle_indices(
+    mesh: &Mesh
+) -> Vec<u32> {
+
+    let mut indices: Vec<u32> = match mesh.indices() {
+        Some(Indices::U16(xs))  => xs.iter().map(|&i| i as u32).collect(),
+        Some(Indices::U32(xs))  => xs.clone(),
+        None                    => (0..mesh.count_vertices() as u32).collect(),
+    };
+
+    let topology = mesh.primitive_topology();
+    if topology == PrimitiveTopology::TriangleStrip {
+        return cpu_mesh_to_gaussians::triangles_from(topology, &indices).flatten().collect();
+    }
+
+    let remainder = indices.len() % 3;
+    if remainder != 0 {
+        warn!(
+            "mesh_triangle_indices: {} indices is not a whole number of triangles; ignoring the last {}",
+            indices.len(),
+            remainder
+        );
+        indices.truncate(indices.len() - remainder);
+    }
+
+    indices
+}
+
+
+
+
+
+
+
+/// The visibility a source mesh had before conversion hid it, restored when it is shown again.
+#[derive(Component, Clone, Copy)]
+struct HiddenSourceVisibility(Visibility);
+
+/// Re-applies `hide_source_mesh` when it is toggled on a source that has already been
+/// converted, so the original mesh can be shown again for comparison.
+fn sync_source_mesh_visibility(
+    mut commands:   Commands,
+    mut source_q:   Query<
+        (Entity, &MeshToGaussian, &mut Visibility, Option<&HiddenSourceVisibility>),
+        (Changed<MeshToGaussian>, With<MeshToGaussianCloud>),
+    >,
+) {
+
+    for (entity, config, mut visibility, hidden) in &mut source_q {
+        match (config.hide_source_mesh, hidden) {
+            (true, None) => {
+                commands.entity(entity).insert(HiddenSourceVisibility(*visibility));
+                *visibility = Visibility::Hidden;
+            }
+            (false, Some(&HiddenSourceVisibility(original))) => {
+                commands.entity(entity).remove::<HiddenSourceVisibility>();
+                *visibility = original;
+            }
+            _ => {}
+        }
+    }
+}
+
+
+
+
+
+
+
+/// Color of the `debug_show_source_wireframe` overlay
+const SOURCE_WIREFRAME_COLOR: Color = Color::srgba(0.2, 1.0, 0.4, 0.6);
+
+/// Draws the triangles of every selected source mesh for sources converted with
+/// `debug_show_source_wireframe` set.
+fn draw_source_wireframes(
+    mut gizmos:     Gizmos,
+    meshes:         Res<Assets<Mesh>>,
+    source_q:       Query<(Entity, &MeshToGaussian), With<MeshToGaussianCloud>>,
+    children_q:     Query<&Children>,
+    mesh_3d_q:      Query<&Mesh3d>,
+    transform_q:    Query<&GlobalTransform>,
+    name_q:         Query<&Name>,
+) {
+
+    for (source_entity, config) in &source_q {
+
+        if !config.debug_show_source_wireframe {
+            continue;
+        }
+
+        let found = find_selected_meshes(
+            source_entity,
+            &config.mesh_selection,
+            &children_q,
+            &mesh_3d_q,
+            &transform_q,
+            &name_q,
+        );
+
+        for (handle, transform) in found {
+
+            let Some(mesh) = meshes.get(&handle) else { continue };
+            let Some(positions) = cpu_mesh_to_gaussians::read_positions(mesh) else { continue };
+
+            let world: Vec<Vec3> = positions.iter().map(|p| transform.transform_point(*p)).collect();
+
+            for tri in mesh_triangle_indices(mesh).chunks_exact(3) {
+                let (Some(&a), Some(&b), Some(&c)) = (
+                    world.get(tri[0] as usize),
+                    world.get(tri[1] as usize),
+                    world.get(tri[2] as usize),
+                ) else {
+                    continue;
+                };
+
+                gizmos.linestrip([a, b, c, a], SOURCE_WIREFRAME_COLOR);
+            }
+        }
+    }
+}
+
+
+
+
+
+
+
+/// Multiplies `GaussianGlobalSettings` into every cloud's own scale and opacity, recording those
+/// as a `CloudBaseSettings` the first time a cloud is seen.
+fn apply_global_cloud_settings(
+    mut commands:   Commands,
+    global:         Res<GaussianGlobalSettings>,
+    mut cloud_q:    Query<(
+        Entity,
+        &mut bevy_gaussian_splatting::CloudSettings,
+        Option<Ref<CloudBaseSettings>>,
+    )>,
+) {
+
+    for (entity, mut cloud, base) in &mut cloud_q {
+
+        let base = match base {
+            Some(base) if !cloud.is_added() => {
+                if !global.is_changed() && !base.is_changed() {
+                    continue;
+                }
+                *base
+            }
+            _ => {
+                let base = CloudBaseSettings {
+                    global_scale:   cloud.global_scale,
+                    global_opacity: cloud.global_opacity,
+                };
+                commands.entity(entity).insert(base);
+                base
+            }
+        };
+
+        cloud.global_scale      = base.global_scale * global.global_scale;
+        cloud.global_opacity    = base.global_opacity * global.global_opacity;
+    }
+}
+
+
+
+/// Sets the global `SortConfig` period to the shortest `CloudSortPeriod` in the scene, and puts
+/// back the period it replaced once no cloud requests one.
+fn apply_cloud_sort_periods(
+    sort_config:    Option<ResMut<bevy_gaussian_splatting::sort::SortConfig>>,
+    period_q:       Query<&CloudSortPeriod>,
+    mut original:   Local<Option<usize>>,
+) {
+
+    let Some(mut sort_config) = sort_config else {
+        return;
+    };
+
+    let Some(period) = period_q.iter().map(|p| p.0).min() else {
+        if let Some(original) = original.take() {
+            sort_config.period_ms = original;
+        }
+        return;
+    };
+
+    original.get_or_insert(sort_config.period_ms);
+
+    if sort_config.period_ms != period {
+        sort_config.period_ms = period;
+    }
+}
+
+
+
+
+
+
+
+/// How often `debug_entities` logs entity counts, in frames. `0` disables the log.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct DebugEntitiesInterval(pub u32);
+
+impl Default for DebugEntitiesInterval {
+    fn default() -> Self {
+        Self(60)
+    }
+}
+
+
+
 /// Debug system to track what entities exist and their components
 fn debug_entities(
+    mut frame_count:    Local<u32>,
+    interval:           Res<DebugEntitiesInterval>,
     q_clouds:           Query<Entity, With<bevy_gaussian_splatting::PlanarGaussian3dHandle>>,
     q_inputs:           Query<Entity, With<gpu_mesh_to_gaussians::TriToSplatCpuInput>>,
     q_mesh_to_gauss:    Query<Entity, With<MeshToGaussian>>,
 ) {
 
+    // Only log periodically to avoid spam
+    *frame_count = frame_count.wrapping_add(1);
+    if interval.0 == 0 || *frame_count % interval.0 != 0 {
+        return;
+    }
+
     let cloud_count             = q_clouds.iter().count();
-    let input_count             = q_inputs.iter().count(); 
+    let input_count             = q_inputs.iter().count();
     let mesh_to_gauss_count     = q_mesh_to_gauss.iter().count();
-    
-    // Only log periodically to avoid spam
-    static mut FRAME_COUNT: u32 = 0;
-    unsafe {
-        FRAME_COUNT += 1;
-        if FRAME_COUNT % 60 == 0 {
-            bevy::log::info!("DEBUG: clouds={}, inputs={}, mesh_to_gauss={}", 
-                cloud_count, input_count, mesh_to_gauss_count);
+
+    bevy::log::info!("DEBUG: clouds={}, inputs={}, mesh_to_gauss={}",
+        cloud_count, input_count, mesh_to_gauss_count);
+}
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use bevy_gaussian_splatting::CloudSettings;
+
+    use super::*;
+
+    #[test]
+    fn global_settings_multiply_each_cloud() {
+        let mut app = App::new();
+        app.insert_resource(GaussianGlobalSettings { global_scale: 3.0, global_opacity: 0.5 });
+        app.add_systems(Update, apply_global_cloud_settings);
+
+        let cloud = app.world_mut()
+            .spawn(CloudSettings { global_scale: 2.0, global_opacity: 0.8, ..default() })
+            .id();
+
+        let settings = |app: &App| {
+            let cloud = app.world().get::<CloudSettings>(cloud).unwrap();
+            (cloud.global_scale, cloud.global_opacity)
+        };
+
+        app.update();
+        assert_eq!(settings(&app), (6.0, 0.4));
+
+        // A second change multiplies the cloud's own values again rather than the last result
+        app.world_mut().resource_mut::<GaussianGlobalSettings>().global_scale = 0.5;
+        app.update();
+        app.update();
+        assert_eq!(settings(&app), (1.0, 0.4));
+
+        app.world_mut().get_mut::<CloudBaseSettings>(cloud).unwrap().global_opacity = 1.0;
+        app.update();
+        assert_eq!(settings(&app), (1.0, 0.5));
+    }
+
+    #[test]
+    fn sort_period_is_restored_after_the_last_request() {
+        use bevy_gaussian_splatting::sort::SortConfig;
+
+        let mut app = App::new();
+        app.init_resource::<SortConfig>();
+        app.add_systems(Update, apply_cloud_sort_periods);
+
+        let period      = |app: &App| app.world().resource::<SortConfig>().period_ms;
+        let original    = period(&app);
+
+        let slow = app.world_mut().spawn(CloudSortPeriod(original + 200)).id();
+        let fast = app.world_mut().spawn(CloudSortPeriod(original + 50)).id();
+        app.update();
+        assert_eq!(period(&app), original + 50);
+
+        app.world_mut().despawn(fast);
+        app.update();
+        assert_eq!(period(&app), original + 200);
+
+        app.world_mut().despawn(slow);
+        app.update();
+        assert_eq!(period(&app), original);
+    }
+
+    #[test]
+    fn showing_the_source_restores_its_visibility() {
+        let mut app = App::new();
+        app.add_systems(Update, sync_source_mesh_visibility);
+
+        let config = MeshToGaussian { hide_source_mesh: false, ..default() };
+        let source = app.world_mut()
+            .spawn((config, MeshToGaussianCloud { handles: Vec::new(), splat_count: 0 }, Visibility::Visible))
+            .id();
+
+        let visibility = |app: &App| *app.world().get::<Visibility>(source).unwrap();
+
+        app.update();
+        assert_eq!(visibility(&app), Visibility::Visible);
+
+        for (hide, expected) in [(true, Visibility::Hidden), (true, Visibility::Hidden), (false, Visibility::Visible)] {
+            app.world_mut().get_mut::<MeshToGaussian>(source).unwrap().hide_source_mesh = hide;
+            app.update();
+            assert_eq!(visibility(&app), expected);
         }
     }
-}
\ No newline at end of file
+}
+
@@ -0,0 +1,75 @@
+// Coherent value noise shared by the procedural generators and cloud animations.
+
+use bevy::prelude::*;
+
+use crate::gaussian::rng::{hash_combine, hash_to_unit};
+
+
+
+
+
+
+
+/// Smooth 2D value noise in [0, 1], interpolated between hashed lattice points.
+pub fn smooth_value_noise(point: Vec2) -> f32 {
+    let cell = point.floor();
+    let frac = point - cell;
+
+    let c00 = lattice_value(cell);
+    let c10 = lattice_value(cell + Vec2::new(1.0, 0.0));
+    let c01 = lattice_value(cell + Vec2::new(0.0, 1.0));
+    let c11 = lattice_value(cell + Vec2::new(1.0, 1.0));
+
+    let fade = frac * frac * (Vec2::splat(3.0) - 2.0 * frac);
+
+    let nx0 = c00 + (c10 - c00) * fade.x;
+    let nx1 = c01 + (c11 - c01) * fade.x;
+
+    let value = nx0 + (nx1 - nx0) * fade.y;
+    value.clamp(0.0, 1.0)
+}
+
+
+
+/// Smooth 3D value noise in [0, 1], the trilinear counterpart of [`smooth_value_noise`].
+pub fn smooth_value_noise_3d(point: Vec3) -> f32 {
+    let cell = point.floor();
+    let frac = point - cell;
+
+    let fade = frac * frac * (Vec3::splat(3.0) - 2.0 * frac);
+
+    let corner = |x: f32, y: f32, z: f32| lattice_value_3d(cell + Vec3::new(x, y, z));
+
+    let nx00 = corner(0.0, 0.0, 0.0).lerp(corner(1.0, 0.0, 0.0), fade.x);
+    let nx10 = corner(0.0, 1.0, 0.0).lerp(corner(1.0, 1.0, 0.0), fade.x);
+    let nx01 = corner(0.0, 0.0, 1.0).lerp(corner(1.0, 0.0, 1.0), fade.x);
+    let nx11 = corner(0.0, 1.0, 1.0).lerp(corner(1.0, 1.0, 1.0), fade.x);
+
+    let nxy0 = nx00.lerp(nx10, fade.y);
+    let nxy1 = nx01.lerp(nx11, fade.y);
+
+    nxy0.lerp(nxy1, fade.z).clamp(0.0, 1.0)
+}
+
+
+
+/// Three decorrelated [`smooth_value_noise_3d`] channels remapped to [-1, 1].
+pub fn noise_vec3(point: Vec3) -> Vec3 {
+    Vec3::new(
+        smooth_value_noise_3d(point + Vec3::new(19.1, 0.0, 0.0)),
+        smooth_value_noise_3d(point + Vec3::new(0.0, 33.4, 0.0)),
+        smooth_value_noise_3d(point + Vec3::new(0.0, 0.0, 47.2)),
+    ) * 2.0
+        - Vec3::ONE
+}
+
+
+
+// Lattice points are whole numbers, so they hash exactly as integers
+fn lattice_value(point: Vec2) -> f32 {
+    hash_to_unit(point.x as i32 as u32, point.y as i32 as u32)
+}
+
+fn lattice_value_3d(point: Vec3) -> f32 {
+    hash_to_unit(hash_combine(point.x as i32 as u32, point.y as i32 as u32), point.z as i32 as u32)
+}
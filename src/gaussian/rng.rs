@@ -0,0 +1,72 @@
+// Integer hashing for reproducible pseudo-random values. Only integer arithmetic is involved,
+// so results are bit-identical across platforms and match the WGSL ports in the shaders.
+
+use bevy::prelude::*;
+
+
+
+
+
+
+
+/// The lowbias32 integer hash.
+pub fn hash_u32(
+    x: u32
+) -> u32 {
+
+    let mut h = x;
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    h
+}
+
+
+
+/// Hash of index `i` in the stream selected by `seed`. Nest calls to hash more than two
+/// values, e.g. `hash_combine(hash_combine(x, y), z)`.
+pub fn hash_combine(
+    seed:   u32,
+    i:      u32,
+) -> u32 {
+
+    hash_u32(i ^ hash_u32(seed))
+}
+
+
+
+/// Uniform value in [0, 1) for index `i` of the stream selected by `seed`.
+pub fn hash_to_unit(
+    seed:   u32,
+    i:      u32,
+) -> f32 {
+
+    unit_from_bits(hash_combine(seed, i))
+}
+
+
+
+/// Three independent uniform values in [0, 1) for index `i` of the stream selected by `seed`.
+pub fn hash_to_vec3(
+    seed:   u32,
+    i:      u32,
+) -> Vec3 {
+
+    let x = hash_combine(seed, i);
+    let y = hash_u32(x);
+    let z = hash_u32(y);
+
+    Vec3::new(unit_from_bits(x), unit_from_bits(y), unit_from_bits(z))
+}
+
+
+
+// Top 24 bits of a hash mapped to [0, 1), exactly representable in an f32.
+fn unit_from_bits(
+    h: u32
+) -> f32 {
+
+    (h >> 8) as f32 / (1u32 << 24) as f32
+}
@@ -1,6 +1,12 @@
+use bevy::prelude::*;
+
+use crate::gaussian::MeshToGaussianMode;
+
 /// Settings for controlling mesh-to-Gaussian conversion
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
 pub struct MeshConversionSettings {
+    /// How faces are turned into gaussians
+    pub mode: MeshToGaussianMode,
     /// Default scale for vertex gaussians
     pub vertex_scale: f32,
     /// Scale for edge gaussians
@@ -9,30 +15,70 @@ pub struct MeshConversionSettings {
     pub face_scale: f32,
     /// Default opacity for all gaussians
     pub opacity: f32,
+    /// How gaussians are colored
+    pub color_mode: ColorMode,
     /// Whether to generate gaussians for vertices
     pub include_vertices: bool,
     /// Whether to generate gaussians for edges
     pub include_edges: bool,
     /// Whether to generate gaussians for faces
     pub include_faces: bool,
+    /// Merge vertices closer than this before generating vertex and edge gaussians; 0 disables welding
+    pub weld_threshold: f32,
+    /// Per-axis (min, max) bounds applied to every generated splat's scale; by default only
+    /// negative scales are clamped
+    pub scale_clamp: (Vec3, Vec3),
+    /// Also return a normal per gaussian from `mesh_to_gaussians_with_normals`, e.g. for export
+    pub with_normals: bool,
+    /// Spherical harmonic degree to write. 0 gives flat color; 1 or more adds a first-order
+    /// band that brightens splats seen head-on and darkens them from behind. Bands above 1 have
+    /// nothing to derive from the normal and stay zero.
+    pub sh_degree: u8,
 }
 
 impl Default for MeshConversionSettings {
     fn default() -> Self {
         Self {
+            mode: MeshToGaussianMode::TrianglesOneToOne,
             vertex_scale: 0.02,
             edge_scale: 0.015,
             face_scale: 0.03,
             opacity: 0.8,
+            color_mode: ColorMode::Normal,
             include_vertices: false,
             include_edges: false,
             include_faces: true,
+            weld_threshold: 0.0,
+            scale_clamp: (Vec3::ZERO, Vec3::splat(f32::INFINITY)),
+            with_normals: false,
+            sh_degree: 0,
+        }
+    }
+}
+
+/// Scene-wide multipliers for every gaussian cloud. Each cloud's `CloudSettings` gets its own
+/// `global_scale` and `global_opacity`, kept in a `CloudBaseSettings`, times these, whenever
+/// this resource changes and when a cloud is spawned.
+#[derive(Resource, Debug, Clone, Reflect)]
+#[reflect(Resource)]
+pub struct GaussianGlobalSettings {
+    /// Multiplier on splat scale
+    pub global_scale: f32,
+    /// Multiplier on splat opacity
+    pub global_opacity: f32,
+}
+
+impl Default for GaussianGlobalSettings {
+    fn default() -> Self {
+        Self {
+            global_scale: 1.0,
+            global_opacity: 1.0,
         }
     }
 }
 
 /// Settings for point cloud to Gaussian conversion
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Reflect)]
 pub struct PointCloudSettings {
     /// Scale for point gaussians
     pub scale: f32,
@@ -40,6 +86,10 @@ pub struct PointCloudSettings {
     pub opacity: f32,
     /// Whether to use provided normals for color (if false, uses position-based color)
     pub use_normals_for_color: bool,
+    /// How point gaussians are colored
+    pub color_mode: ColorMode,
+    /// Spherical harmonic degree to write; see `MeshConversionSettings::sh_degree`
+    pub sh_degree: u8,
 }
 
 impl Default for PointCloudSettings {
@@ -48,19 +98,25 @@ impl Default for PointCloudSettings {
             scale: 0.02,
             opacity: 0.8,
             use_normals_for_color: true,
+            color_mode: ColorMode::Normal,
+            sh_degree: 0,
         }
     }
 }
 
 /// Color mode for Gaussian generation
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Reflect)]
 pub enum ColorMode {
     /// Use surface normals to derive color
     Normal,
     /// Use a solid color for all gaussians
     Solid([f32; 3]),
-    /// Use position-based color gradient
+    /// Use position-based color gradient along the longest axis of the source bounds
     Gradient { from: [f32; 3], to: [f32; 3] },
-    /// Use random colors
-    Random,
+    /// Use random colors hashed from each gaussian's position and `seed`: the same seed always
+    /// gives the same colors, a different one reshuffles them
+    Random { seed: u32 },
+    /// Use colors supplied alongside the points, e.g. from an imported PLY. Sources without
+    /// per-point colors fall back to `Normal`.
+    PerPoint,
 }
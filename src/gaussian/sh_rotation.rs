@@ -0,0 +1,209 @@
+// Rotation of real spherical harmonic coefficients, used by the SH-aware cloud interpolation.
+//
+// Assumed layout: degree 3, 16 coefficients per channel stored interleaved as
+// `coefficients[coeff * 3 + channel]`, so the DC term occupies indices 0..3. The basis and sign
+// conventions are the ones the renderer evaluates (the reference 3DGS constants below).
+
+use bevy::prelude::*;
+use bevy_gaussian_splatting::SphericalHarmonicCoefficients;
+
+
+
+
+
+
+
+const SH_C1: f32 = 0.488_602_5;
+const SH_C2: [f32; 5] = [1.092_548_4, -1.092_548_4, 0.315_391_57, -1.092_548_4, 0.546_274_2];
+const SH_C3: [f32; 7] = [
+    -0.590_043_6,
+    2.890_611_4,
+    -0.457_045_8,
+    0.373_176_33,
+    -0.457_045_8,
+    1.445_305_7,
+    -0.590_043_6,
+];
+
+/// Number of coefficients per channel for degree 3.
+const SH_PER_CHANNEL: usize = 16;
+
+/// Directions the band rotation matrices are fitted on. Comfortably more than the 7 unknowns of
+/// the largest band so the least-squares fit is well conditioned.
+const FIT_SAMPLES: usize = 32;
+
+
+
+
+
+
+
+/// Precomputed sample directions and basis values shared across every rotation in a cloud.
+pub struct ShRotationFit {
+    directions: Vec<Vec3>,
+    basis:      Vec<[f32; SH_PER_CHANNEL]>,
+}
+
+impl Default for ShRotationFit {
+    fn default() -> Self {
+        // Fibonacci sphere: near-uniform coverage without clustering at the poles
+        let golden = std::f32::consts::PI * (3.0 - 5.0_f32.sqrt());
+
+        let directions: Vec<Vec3> = (0..FIT_SAMPLES)
+            .map(|i| {
+                let y   = 1.0 - 2.0 * (i as f32 + 0.5) / FIT_SAMPLES as f32;
+                let r   = (1.0 - y * y).sqrt();
+                let phi = golden * i as f32;
+                Vec3::new(r * phi.cos(), y, r * phi.sin())
+            })
+            .collect();
+
+        let basis = directions.iter().map(|d| sh_basis(*d)).collect();
+
+        Self { directions, basis }
+    }
+}
+
+impl ShRotationFit {
+
+    /// Rotate the view-dependent bands of `sh` by `rotation`, so that the rotated coefficients
+    /// evaluated along `d` equal the originals evaluated along `rotation⁻¹ · d`.
+    ///
+    /// The DC term is rotation invariant and copied as-is. Bands that don't fit in the
+    /// coefficient array (lower-degree builds) are skipped.
+    pub fn rotate(
+        &self,
+        sh:         &SphericalHarmonicCoefficients,
+        rotation:   Quat,
+    ) -> SphericalHarmonicCoefficients {
+
+        let mut out = *sh;
+
+        let per_channel = (sh.coefficients.len() / 3).min(SH_PER_CHANNEL);
+        let inverse     = rotation.inverse();
+
+        let rotated_basis: Vec<[f32; SH_PER_CHANNEL]> = self.directions
+            .iter()
+            .map(|d| sh_basis(inverse * *d))
+            .collect();
+
+        // Rotations never mix bands, so each band is fitted independently
+        for band in 1..4 {
+            let start   = band * band;
+            let end     = (band + 1) * (band + 1);
+            if end > per_channel {
+                break;
+            }
+            let size = end - start;
+
+            // Normal equations AᵀA · c' = Aᵀ · f, where f samples the rotated function
+            let mut normal  = [[0.0_f32; 7]; 7];
+            let mut rhs     = [[0.0_f32; 3]; 7];
+
+            for (a, b) in self.basis.iter().zip(&rotated_basis) {
+                let mut value = [0.0_f32; 3];
+                for m in start..end {
+                    for (c, v) in value.iter_mut().enumerate() {
+                        *v += b[m] * sh.coefficients[m * 3 + c];
+                    }
+                }
+
+                for i in 0..size {
+                    for j in 0..size {
+                        normal[i][j] += a[start + i] * a[start + j];
+                    }
+                    for c in 0..3 {
+                        rhs[i][c] += a[start + i] * value[c];
+                    }
+                }
+            }
+
+            solve_in_place(&mut normal, &mut rhs, size);
+
+            for i in 0..size {
+                for c in 0..3 {
+                    out.coefficients[(start + i) * 3 + c] = rhs[i][c];
+                }
+            }
+        }
+
+        out
+    }
+}
+
+
+
+
+
+
+
+/// Real SH basis up to degree 3 in renderer order, evaluated for a unit direction.
+fn sh_basis(dir: Vec3) -> [f32; SH_PER_CHANNEL] {
+    let Vec3 { x, y, z } = dir;
+    let (xx, yy, zz) = (x * x, y * y, z * z);
+
+    [
+        0.282_094_8,
+        -SH_C1 * y,
+        SH_C1 * z,
+        -SH_C1 * x,
+        SH_C2[0] * x * y,
+        SH_C2[1] * y * z,
+        SH_C2[2] * (2.0 * zz - xx - yy),
+        SH_C2[3] * x * z,
+        SH_C2[4] * (xx - yy),
+        SH_C3[0] * y * (3.0 * xx - yy),
+        SH_C3[1] * x * y * z,
+        SH_C3[2] * y * (4.0 * zz - xx - yy),
+        SH_C3[3] * z * (2.0 * zz - 3.0 * xx - 3.0 * yy),
+        SH_C3[4] * x * (4.0 * zz - xx - yy),
+        SH_C3[5] * z * (xx - yy),
+        SH_C3[6] * x * (xx - 3.0 * yy),
+    ]
+}
+
+
+
+/// Gaussian elimination with partial pivoting on the leading `size`×`size` block.
+/// The solution for each of the three channels is left in `rhs`.
+fn solve_in_place(
+    matrix: &mut [[f32; 7]; 7],
+    rhs:    &mut [[f32; 3]; 7],
+    size:   usize,
+) {
+
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|&a, &b| matrix[a][col].abs().total_cmp(&matrix[b][col].abs()))
+            .unwrap_or(col);
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+
+        let diag = matrix[col][col];
+        if diag.abs() < f32::EPSILON {
+            continue;
+        }
+
+        for row in 0..size {
+            if row == col {
+                continue;
+            }
+            let factor = matrix[row][col] / diag;
+            for k in col..size {
+                matrix[row][k] -= factor * matrix[col][k];
+            }
+            for c in 0..3 {
+                rhs[row][c] -= factor * rhs[col][c];
+            }
+        }
+    }
+
+    for row in 0..size {
+        let diag = matrix[row][row];
+        if diag.abs() >= f32::EPSILON {
+            for c in 0..3 {
+                rhs[row][c] /= diag;
+            }
+        }
+    }
+}
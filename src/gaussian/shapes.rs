@@ -0,0 +1,295 @@
+// Parametric point sources. Each shape has a `*_points` and a matching `*_normals` function
+// that sample the same parameters, so index `i` of one lines up with index `i` of the other
+// and both can be passed straight to `points_to_gaussians`. Points on the unit sphere are their
+// own outward normals, so the sphere samplers only have the one function.
+
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+/// Parameter step for the finite-difference tangents.
+const TANGENT_STEP: f32 = 1e-3;
+
+/// Golden angle in radians, used to spread samples over a closed surface.
+const GOLDEN_ANGLE: f32 = 2.399_963_2;
+
+
+
+
+
+
+
+/// `count` points evenly spaced in parameter along a (p, q) torus knot.
+///
+/// The knot winds `p` times around the axis of a torus with major radius 2 and minor radius 1
+/// lying in the XY plane, and `q` times through its hole. Coprime `p` and `q` give a true knot
+/// (e.g. 2, 3 is the trefoil); otherwise the curve closes early and repeats itself.
+pub fn torus_knot_points(
+    p:      u32,
+    q:      u32,
+    count:  usize,
+) -> Vec<Vec3> {
+
+    (0..count)
+        .map(|i| torus_knot(p, q, torus_knot_parameter(i, count)))
+        .collect()
+}
+
+
+
+/// Normals matching [`torus_knot_points`]: the outward normal of the underlying torus,
+/// made orthogonal to the curve tangent.
+pub fn torus_knot_normals(
+    p:      u32,
+    q:      u32,
+    count:  usize,
+) -> Vec<Vec3> {
+
+    (0..count)
+        .map(|i| {
+            let phi     = torus_knot_parameter(i, count);
+            let point   = torus_knot(p, q, phi);
+
+            let tangent = (torus_knot(p, q, phi + TANGENT_STEP) - torus_knot(p, q, phi - TANGENT_STEP))
+                .normalize_or_zero();
+
+            // The knot lies on the torus, so the direction away from the core circle is outward
+            let core    = Vec3::new((p as f32 * phi).cos(), (p as f32 * phi).sin(), 0.0) * 2.0;
+            let outward = point - core;
+
+            (outward - tangent * outward.dot(tangent)).normalize_or_zero()
+        })
+        .collect()
+}
+
+
+
+fn torus_knot_parameter(
+    i:      usize,
+    count:  usize,
+) -> f32 {
+
+    TAU * i as f32 / count.max(1) as f32
+}
+
+
+
+fn torus_knot(
+    p:      u32,
+    q:      u32,
+    phi:    f32,
+) -> Vec3 {
+
+    let (p, q)  = (p as f32, q as f32);
+    let r       = (q * phi).cos() + 2.0;
+
+    Vec3::new(
+        r * (p * phi).cos(),
+        r * (p * phi).sin(),
+        -(q * phi).sin(),
+    )
+}
+
+
+
+
+
+
+
+/// `count` points on the surface of a unit superellipsoid with Y as its polar axis.
+///
+/// `n1` shapes the profile from pole to pole and `n2` the cross-section around the axis:
+/// 1 is round, values towards 0 become boxy and 2 gives straight-sided diamonds. Samples follow
+/// a Fibonacci spiral in parameter space, so they are roughly even for shapes close to a sphere.
+pub fn superellipsoid_points(
+    n1:     f32,
+    n2:     f32,
+    count:  usize,
+) -> Vec<Vec3> {
+
+    (0..count)
+        .map(|i| {
+            let (u, v) = fibonacci_parameters(i, count);
+            superellipsoid(n1, n2, u, v)
+        })
+        .collect()
+}
+
+
+
+/// Normals matching [`superellipsoid_points`], from the cross product of the two parameter
+/// tangents. Falls back to the radial direction at the poles, where the tangents degenerate.
+pub fn superellipsoid_normals(
+    n1:     f32,
+    n2:     f32,
+    count:  usize,
+) -> Vec<Vec3> {
+
+    (0..count)
+        .map(|i| {
+            let (u, v)  = fibonacci_parameters(i, count);
+            let point   = superellipsoid(n1, n2, u, v);
+
+            let du      = superellipsoid(n1, n2, u + TANGENT_STEP, v) - superellipsoid(n1, n2, u - TANGENT_STEP, v);
+            let dv      = superellipsoid(n1, n2, u, v + TANGENT_STEP) - superellipsoid(n1, n2, u, v - TANGENT_STEP);
+
+            let normal  = dv.cross(du).normalize_or_zero();
+            if normal == Vec3::ZERO {
+                return point.normalize_or_zero();
+            }
+
+            // Keep it pointing away from the center
+            if normal.dot(point) < 0.0 { -normal } else { normal }
+        })
+        .collect()
+}
+
+
+
+// Longitude `u` and latitude `v` of sample `i` on a Fibonacci spiral.
+fn fibonacci_parameters(
+    i:      usize,
+    count:  usize,
+) -> (f32, f32) {
+
+    let t = (i as f32 + 0.5) / count.max(1) as f32;
+
+    let u = (i as f32 * GOLDEN_ANGLE) % TAU;
+    let v = (1.0 - 2.0 * t).clamp(-1.0, 1.0).asin();
+
+    (u, v)
+}
+
+
+
+fn superellipsoid(
+    n1: f32,
+    n2: f32,
+    u:  f32,
+    v:  f32,
+) -> Vec3 {
+
+    let ring = signed_pow(v.cos(), n1);
+
+    Vec3::new(
+        ring * signed_pow(u.cos(), n2),
+        signed_pow(v.sin(), n1),
+        ring * signed_pow(u.sin(), n2),
+    )
+}
+
+
+
+fn signed_pow(
+    x:          f32,
+    exponent:   f32,
+) -> f32 {
+
+    x.signum() * x.abs().powf(exponent)
+}
+
+
+
+
+
+
+
+/// `count` points spread evenly over the unit sphere along a Fibonacci spiral around Y.
+///
+/// Every point is also its own outward normal.
+pub fn fibonacci_sphere(
+    count: usize
+) -> Vec<Vec3> {
+
+    (0..count)
+        .map(|i| {
+            let (u, v) = fibonacci_parameters(i, count);
+            Vec3::new(v.cos() * u.cos(), v.sin(), v.cos() * u.sin())
+        })
+        .collect()
+}
+
+
+
+/// Vertices of an icosahedron projected onto the unit sphere after `subdivisions` rounds of
+/// splitting every triangle into four: `10 * 4^subdivisions + 2` points (12, 42, 162, ...).
+///
+/// Every point is also its own outward normal.
+pub fn icosphere_points(
+    subdivisions: u32
+) -> Vec<Vec3> {
+
+    let t = (1.0 + 5f32.sqrt()) * 0.5;
+
+    let mut points: Vec<Vec3> = [
+        [-1.0,  t,  0.0], [ 1.0,  t,  0.0], [-1.0, -t,  0.0], [ 1.0, -t,  0.0],
+        [ 0.0, -1.0,  t], [ 0.0,  1.0,  t], [ 0.0, -1.0, -t], [ 0.0,  1.0, -t],
+        [  t,  0.0, -1.0], [  t,  0.0,  1.0], [ -t,  0.0, -1.0], [ -t,  0.0,  1.0],
+    ]
+    .into_iter()
+    .map(|p| Vec3::from_array(p).normalize())
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Midpoints are shared by the two faces on either side of an edge
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| -> u32 {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                points.push((points[a as usize] + points[b as usize]).normalize());
+                (points.len() - 1) as u32
+            })
+        };
+
+        faces = faces
+            .iter()
+            .flat_map(|&[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    points
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fibonacci_sphere_is_unit_and_even() {
+        let count   = 4096;
+        let points  = fibonacci_sphere(count);
+
+        assert_eq!(points.len(), count);
+        assert!(points.iter().all(|p| (p.length() - 1.0).abs() < 1e-5));
+
+        // Every octant holds an eighth of the points, and they balance out around the center
+        let mut octants = [0usize; 8];
+        for p in &points {
+            octants[(p.x > 0.0) as usize | ((p.y > 0.0) as usize) << 1 | ((p.z > 0.0) as usize) << 2] += 1;
+        }
+
+        let expected = count as f32 / 8.0;
+        for octant in octants {
+            assert!((octant as f32 - expected).abs() < expected * 0.05, "{octants:?}");
+        }
+
+        let mean = points.iter().sum::<Vec3>() / count as f32;
+        assert!(mean.length() < 1e-2, "{mean}");
+    }
+}
@@ -0,0 +1,201 @@
+// Spatial hashing over splat centers, shared by cloud processing that needs neighbor queries.
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_gaussian_splatting::PlanarGaussian3d;
+
+
+
+
+
+
+
+/// Splat indices bucketed into a uniform grid of cubic cells, keyed by `floor(position / cell)`.
+///
+/// Only occupied cells are stored, so sparse clouds over large bounds stay cheap. Indices refer
+/// to the cloud the grid was built from; the grid does not follow later edits to it.
+#[derive(Debug, Clone)]
+pub struct SplatGrid {
+    cell_size:  f32,
+    positions:  Vec<Vec3>,
+    cells:      HashMap<IVec3, Vec<u32>>,
+}
+
+impl SplatGrid {
+
+    /// Bucket every splat of `cloud`, visible or not, into cells of `cell_size`.
+    ///
+    /// Queries are fastest when `cell_size` is close to the radius they use.
+    pub fn new(
+        cloud:      &PlanarGaussian3d,
+        cell_size:  f32,
+    ) -> Self {
+
+        let positions: Vec<Vec3> = cloud.position_visibility
+            .iter()
+            .map(|pv| Vec3::from(pv.position))
+            .collect();
+
+        Self::from_positions(positions, cell_size)
+    }
+
+
+
+    /// Build a grid over bare positions, e.g. the centers of a `&[Gaussian3d]`.
+    pub fn from_positions(
+        positions:  Vec<Vec3>,
+        cell_size:  f32,
+    ) -> Self {
+
+        let cell_size = cell_size.max(f32::EPSILON);
+        let mut cells: HashMap<IVec3, Vec<u32>> = HashMap::new();
+
+        for (index, &position) in positions.iter().enumerate() {
+            cells
+                .entry(cell_key(position, cell_size))
+                .or_default()
+                .push(index as u32);
+        }
+
+        Self { cell_size, positions, cells }
+    }
+
+
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Number of splats in the grid
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Center of the splat at `index`, as it was when the grid was built
+    pub fn position(&self, index: u32) -> Vec3 {
+        self.positions[index as usize]
+    }
+
+    /// Cell containing `position`
+    pub fn cell_of(&self, position: Vec3) -> IVec3 {
+        cell_key(position, self.cell_size)
+    }
+
+    /// Indices bucketed in `cell`, empty when it holds no splats
+    pub fn cell(&self, cell: IVec3) -> &[u32] {
+        self.cells.get(&cell).map_or(&[], Vec::as_slice)
+    }
+
+    /// Occupied cells and the indices in each, in no particular order
+    pub fn cells(&self) -> impl Iterator<Item = (IVec3, &[u32])> {
+        self.cells.iter().map(|(key, indices)| (*key, indices.as_slice()))
+    }
+
+
+
+    /// Indices of the splats whose centers lie within `radius` of `position`, including a splat
+    /// at `position` itself. Order is unspecified; use `take` to cap the number of neighbors.
+    pub fn neighbors_within(
+        &self,
+        position:   Vec3,
+        radius:     f32,
+    ) -> impl Iterator<Item = u32> + '_ {
+
+        let radius      = radius.max(0.0);
+        let radius_sq   = radius * radius;
+        let lo          = self.cell_of(position - radius);
+        let hi          = self.cell_of(position + radius);
+
+        (lo.z..=hi.z)
+            .flat_map(move |z| (lo.y..=hi.y).flat_map(move |y| (lo.x..=hi.x).map(move |x| IVec3::new(x, y, z))))
+            .flat_map(|key| self.cell(key).iter().copied())
+            .filter(move |&index| self.position(index).distance_squared(position) <= radius_sq)
+    }
+
+
+
+    /// Index of the splat closest to `position` within `max_radius`, if any.
+    pub fn nearest(
+        &self,
+        position:   Vec3,
+        max_radius: f32,
+    ) -> Option<u32> {
+
+        self.neighbors_within(position, max_radius)
+            .min_by(|&a, &b| {
+                let da = self.position(a).distance_squared(position);
+                let db = self.position(b).distance_squared(position);
+                da.total_cmp(&db)
+            })
+    }
+}
+
+
+
+fn cell_key(
+    position:   Vec3,
+    cell_size:  f32,
+) -> IVec3 {
+
+    (position / cell_size).floor().as_ivec3()
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use bevy_gaussian_splatting::Gaussian3d;
+
+    use super::*;
+    use crate::gaussian::cpu_transform::to_planar;
+
+    // A 10x10x10 lattice with a spacing that doesn't line up with the cells
+    fn lattice() -> Vec<Vec3> {
+        (0..1000)
+            .map(|i| Vec3::new((i % 10) as f32, ((i / 10) % 10) as f32, (i / 100) as f32) * 0.37 - 1.0)
+            .collect()
+    }
+
+    #[test]
+    fn neighbors_match_brute_force() {
+        let positions   = lattice();
+        let grid        = SplatGrid::from_positions(positions.clone(), 0.5);
+
+        for query in [Vec3::ZERO, Vec3::splat(-1.3), Vec3::new(0.7, 0.2, 1.9)] {
+            for radius in [0.0, 0.3, 0.8, 2.0] {
+                let mut found: Vec<u32> = grid.neighbors_within(query, radius).collect();
+                found.sort_unstable();
+
+                let expected: Vec<u32> = (0..positions.len() as u32)
+                    .filter(|&i| positions[i as usize].distance(query) <= radius)
+                    .collect();
+
+                assert_eq!(found, expected, "query {query} radius {radius}");
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_finds_the_closest_splat() {
+        let positions   = lattice();
+        let grid        = SplatGrid::from_positions(positions.clone(), 0.5);
+
+        assert_eq!(grid.nearest(positions[123] + 0.01, 0.2), Some(123));
+        assert_eq!(grid.nearest(Vec3::splat(100.0), 1.0), None);
+    }
+
+    #[test]
+    fn coincident_splats_share_a_cell() {
+        let cloud = to_planar(&vec![Gaussian3d::default(); 3]);
+        assert_eq!(SplatGrid::new(&cloud, 1.0).cell(IVec3::ZERO), &[0, 1, 2]);
+    }
+}
@@ -0,0 +1,101 @@
+// Spawn helpers that turn CPU-generated gaussians into renderable cloud entities.
+
+use bevy::prelude::*;
+use bevy_gaussian_splatting::{
+    sort::SortMode,
+    CloudSettings,
+    Gaussian3d,
+    PlanarGaussian3d,
+    PlanarGaussian3dHandle,
+};
+
+use crate::gaussian::{cpu_transform::to_planar, CloudSortPeriod};
+
+
+
+
+
+
+
+/// Extension trait for spawning CPU-generated gaussians as a cloud entity.
+pub trait SpawnGaussianCloud {
+    /// Transpose `gaussians` into a `PlanarGaussian3d`, add it to `clouds` and spawn an entity
+    /// rendering it with default `CloudSettings`. Returns the spawned entity.
+    fn spawn_gaussian_cloud(
+        &mut self,
+        gaussians:  &[Gaussian3d],
+        clouds:     &mut Assets<PlanarGaussian3d>,
+    ) -> Entity;
+
+    /// Like [`spawn_gaussian_cloud`](Self::spawn_gaussian_cloud), but rendered with `settings`,
+    /// e.g. to turn off `opacity_adaptive_radius`.
+    fn spawn_gaussian_cloud_with_settings(
+        &mut self,
+        gaussians:  &[Gaussian3d],
+        clouds:     &mut Assets<PlanarGaussian3d>,
+        settings:   CloudSettings,
+    ) -> Entity;
+
+    /// Like [`spawn_gaussian_cloud`](Self::spawn_gaussian_cloud), but sorted with `sort_mode`
+    /// and, if given, carrying a [`CloudSortPeriod`] of `sort_period_ms`.
+    fn spawn_gaussian_cloud_with_sort(
+        &mut self,
+        gaussians:      &[Gaussian3d],
+        clouds:         &mut Assets<PlanarGaussian3d>,
+        sort_mode:      SortMode,
+        sort_period_ms: Option<usize>,
+    ) -> Entity;
+}
+
+impl SpawnGaussianCloud for Commands<'_, '_> {
+
+    fn spawn_gaussian_cloud(
+        &mut self,
+        gaussians:  &[Gaussian3d],
+        clouds:     &mut Assets<PlanarGaussian3d>,
+    ) -> Entity {
+
+        self.spawn_gaussian_cloud_with_settings(gaussians, clouds, CloudSettings::default())
+    }
+
+
+
+    fn spawn_gaussian_cloud_with_settings(
+        &mut self,
+        gaussians:  &[Gaussian3d],
+        clouds:     &mut Assets<PlanarGaussian3d>,
+        settings:   CloudSettings,
+    ) -> Entity {
+
+        let handle = clouds.add(to_planar(gaussians));
+
+        self.spawn((
+            PlanarGaussian3dHandle(handle),
+            settings,
+            Name::new("GeneratedGaussianCloud"),
+            Transform::default(),
+            Visibility::Visible,
+        ))
+        .id()
+    }
+
+
+
+    fn spawn_gaussian_cloud_with_sort(
+        &mut self,
+        gaussians:      &[Gaussian3d],
+        clouds:         &mut Assets<PlanarGaussian3d>,
+        sort_mode:      SortMode,
+        sort_period_ms: Option<usize>,
+    ) -> Entity {
+
+        let settings    = CloudSettings { sort_mode, ..default() };
+        let entity      = self.spawn_gaussian_cloud_with_settings(gaussians, clouds, settings);
+
+        if let Some(period) = sort_period_ms {
+            self.entity(entity).insert(CloudSortPeriod(period));
+        }
+
+        entity
+    }
+}
@@ -0,0 +1,435 @@
+// Summary statistics over a cloud, for framing cameras and sanity-checking conversions.
+
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_gaussian_splatting::{PlanarGaussian3d, PlanarGaussian3dHandle};
+
+use crate::gaussian::gpu_mesh_to_gaussians::TriToSplatCpuInput;
+
+
+
+
+
+
+
+/// Aggregate shape of a cloud. Every field is zero for an empty cloud.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+pub struct CloudStats {
+    pub count:          usize,
+    pub bounds_min:     Vec3,
+    pub bounds_max:     Vec3,
+    pub mean_position:  Vec3,
+    /// Per-axis mean of the splat scales
+    pub mean_scale:     Vec3,
+    pub mean_opacity:   f32,
+}
+
+impl CloudStats {
+
+    pub fn center(&self) -> Vec3 {
+        (self.bounds_min + self.bounds_max) * 0.5
+    }
+
+    pub fn size(&self) -> Vec3 {
+        self.bounds_max - self.bounds_min
+    }
+}
+
+
+
+/// Count, bounds and means of a cloud's positions, scales and opacities.
+///
+/// Splats are counted whether or not they are visible, since visibility is a render-time flag.
+pub fn compute_stats(
+    cloud: &PlanarGaussian3d
+) -> CloudStats {
+
+    let count = cloud.position_visibility.len();
+    if count == 0 {
+        return CloudStats::default();
+    }
+
+    let mut bounds_min      = Vec3::splat(f32::MAX);
+    let mut bounds_max      = Vec3::splat(f32::MIN);
+    let mut position_sum    = Vec3::ZERO;
+
+    for pv in &cloud.position_visibility {
+        let position = Vec3::from(pv.position);
+
+        bounds_min      = bounds_min.min(position);
+        bounds_max      = bounds_max.max(position);
+        position_sum   += position;
+    }
+
+    let mut scale_sum       = Vec3::ZERO;
+    let mut opacity_sum     = 0.0;
+
+    for so in &cloud.scale_opacity {
+        scale_sum      += Vec3::from(so.scale);
+        opacity_sum    += so.opacity;
+    }
+
+    let n = count as f32;
+
+    CloudStats {
+        count,
+        bounds_min,
+        bounds_max,
+        mean_position:  position_sum / n,
+        mean_scale:     scale_sum / n,
+        mean_opacity:   opacity_sum / n,
+    }
+}
+
+
+
+/// Oriented bounding box of a cloud's splat centers as `(center, orientation, half_extents)`.
+///
+/// The axes are the principal components of the positions: the eigenvectors of their
+/// covariance, with the local X axis along the largest spread and Z along the smallest. The box
+/// is then fitted tightly along those axes, so `center` is generally not the mean position.
+/// An empty cloud gives a zero-sized box at the origin with no rotation.
+pub fn cloud_obb(
+    cloud: &PlanarGaussian3d
+) -> (Vec3, Quat, Vec3) {
+
+    let count = cloud.position_visibility.len();
+    if count == 0 {
+        return (Vec3::ZERO, Quat::IDENTITY, Vec3::ZERO);
+    }
+
+    let positions   = cloud.position_visibility.iter().map(|pv| Vec3::from(pv.position));
+    let mean        = positions.clone().sum::<Vec3>() / count as f32;
+
+    let covariance = positions.clone().fold(Mat3::ZERO, |sum, p| {
+        let d = p - mean;
+        sum + Mat3::from_cols(d * d.x, d * d.y, d * d.z)
+    }) * (1.0 / count as f32);
+
+    let (eigenvalues, eigenvectors) = symmetric_eigen(covariance);
+
+    let mut axes = [0, 1, 2];
+    axes.sort_by(|&a, &b| eigenvalues[b].total_cmp(&eigenvalues[a]));
+
+    // Keep the basis right-handed so it is a rotation
+    let x_axis  = eigenvectors.col(axes[0]).normalize();
+    let y_axis  = eigenvectors.col(axes[1]).normalize();
+    let basis   = Mat3::from_cols(x_axis, y_axis, x_axis.cross(y_axis));
+
+    let (local_min, local_max) = positions.fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), p| {
+            let local = basis.transpose() * (p - mean);
+            (min.min(local), max.max(local))
+        },
+    );
+
+    let center          = mean + basis * ((local_min + local_max) * 0.5);
+    let half_extents    = (local_max - local_min) * 0.5;
+
+    (center, Quat::from_mat3(&basis).normalize(), half_extents)
+}
+
+
+
+// Eigenvalues and eigenvectors (as columns) of a symmetric matrix by cyclic Jacobi rotations.
+fn symmetric_eigen(
+    matrix: Mat3
+) -> (Vec3, Mat3) {
+
+    // Jacobi converges quadratically; a 3x3 is diagonal to f32 precision well within this
+    const MAX_SWEEPS: usize = 16;
+
+    let mut a = matrix;
+    let mut v = Mat3::IDENTITY;
+
+    for _ in 0..MAX_SWEEPS {
+        let off_diagonal = a.y_axis.x.abs() + a.z_axis.x.abs() + a.z_axis.y.abs();
+        if off_diagonal <= f32::MIN_POSITIVE {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            let apq = a.col(q)[p];
+            if apq.abs() <= f32::MIN_POSITIVE {
+                continue;
+            }
+
+            // Rotation in the (p, q) plane that zeroes a[p][q]
+            let theta   = (a.col(q)[q] - a.col(p)[p]) / (2.0 * apq);
+            let t       = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c       = 1.0 / (t * t + 1.0).sqrt();
+            let s       = t * c;
+
+            let mut rotation = Mat3::IDENTITY;
+            rotation.col_mut(p)[p] = c;
+            rotation.col_mut(q)[q] = c;
+            rotation.col_mut(q)[p] = s;
+            rotation.col_mut(p)[q] = -s;
+
+            a = rotation.transpose() * a * rotation;
+            v *= rotation;
+        }
+    }
+
+    (Vec3::new(a.x_axis.x, a.y_axis.y, a.z_axis.z), v)
+}
+
+
+
+/// Count of splat opacities in `N` equal bins over `[0, 1]`; opacities outside are clamped into
+/// the first or last bin. E.g. `opacity_histogram::<20>(&cloud)`.
+pub fn opacity_histogram<const N: usize>(
+    cloud: &PlanarGaussian3d
+) -> [u32; N] {
+
+    let mut bins = [0u32; N];
+    if N == 0 {
+        return bins;
+    }
+
+    for so in &cloud.scale_opacity {
+        let bin = (so.opacity.clamp(0.0, 1.0) * N as f32) as usize;
+        bins[bin.min(N - 1)] += 1;
+    }
+
+    bins
+}
+
+
+
+
+
+
+
+/// Whether `log_new_cloud_stats` reports each newly spawned cloud. Off by default.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct LogCloudStats(pub bool);
+
+
+
+/// Debug system that logs [`CloudStats`] once for every entity that gains a cloud handle, as
+/// soon as its asset is loaded. Clouds that the GPU conversion fills are skipped: their asset is
+/// only a zeroed placeholder on the CPU side.
+pub fn log_new_cloud_stats(
+    enabled:        Res<LogCloudStats>,
+    clouds:         Res<Assets<PlanarGaussian3d>>,
+    added:          Query<Entity, (Added<PlanarGaussian3dHandle>, Without<TriToSplatCpuInput>)>,
+    handles:        Query<&PlanarGaussian3dHandle>,
+    mut pending:    Local<Vec<Entity>>,
+) {
+
+    if !enabled.0 {
+        pending.clear();
+        return;
+    }
+
+    pending.extend(&added);
+
+    // Keep waiting on clouds whose asset hasn't loaded yet
+    pending.retain(|&entity| {
+        let Ok(handle) = handles.get(entity) else {
+            return false;
+        };
+        let Some(cloud) = clouds.get(&handle.0) else {
+            return true;
+        };
+
+        let stats = compute_stats(cloud);
+
+        info!(
+            "Cloud {entity:?}: {} splats, bounds {:?}..{:?}, mean position {:?}, mean scale {:?}, mean opacity {:.3}",
+            stats.count, stats.bounds_min, stats.bounds_max, stats.mean_position, stats.mean_scale, stats.mean_opacity,
+        );
+
+        false
+    });
+}
+
+
+
+
+
+
+
+/// Point the camera at the cloud's opacity-weighted center from far enough away that the whole
+/// cloud fits in view, assuming Bevy's default perspective projection. See
+/// [`focus_perspective_camera_on_cloud`].
+pub fn focus_camera_on_cloud(
+    camera_transform:   &mut Transform,
+    cloud:              &PlanarGaussian3d,
+    padding:            f32,
+) {
+
+    focus_perspective_camera_on_cloud(camera_transform, &PerspectiveProjection::default(), cloud, padding);
+}
+
+
+
+/// Frame the cloud with a perspective camera, keeping its current viewing direction.
+///
+/// The camera looks at the opacity-weighted centroid and backs off until a sphere around every
+/// visible, non-transparent splat fits both the vertical and horizontal field of view. `padding`
+/// enlarges that sphere as a fraction of its radius, e.g. 0.1 for a 10% margin. Empty or fully
+/// transparent clouds leave the camera unchanged.
+pub fn focus_perspective_camera_on_cloud(
+    camera_transform:   &mut Transform,
+    projection:         &PerspectiveProjection,
+    cloud:              &PlanarGaussian3d,
+    padding:            f32,
+) {
+
+    let Some((center, radius)) = focus_sphere(cloud, padding) else {
+        return;
+    };
+
+    let half_fov_y  = projection.fov * 0.5;
+    let half_fov_x  = (half_fov_y.tan() * projection.aspect_ratio).atan();
+    let distance    = radius / half_fov_y.min(half_fov_x).sin().max(f32::EPSILON);
+
+    look_at_from(camera_transform, center, distance);
+}
+
+
+
+/// Frame the cloud with an orthographic camera, keeping its current viewing direction.
+///
+/// Like [`focus_perspective_camera_on_cloud`], but the camera sits just outside the padded
+/// sphere and the projection is switched to a scaling mode that fits the sphere's diameter.
+pub fn focus_orthographic_camera_on_cloud(
+    camera_transform:   &mut Transform,
+    projection:         &mut OrthographicProjection,
+    cloud:              &PlanarGaussian3d,
+    padding:            f32,
+) {
+
+    let Some((center, radius)) = focus_sphere(cloud, padding) else {
+        return;
+    };
+
+    projection.scale        = 1.0;
+    projection.scaling_mode = ScalingMode::AutoMin { min_width: radius * 2.0, min_height: radius * 2.0 };
+
+    // Keep the whole sphere in front of the near plane
+    look_at_from(camera_transform, center, radius + projection.near.max(0.0) + 1.0);
+}
+
+
+
+// Opacity-weighted centroid and the padded radius around every visible splat that isn't fully
+// transparent.
+fn focus_sphere(
+    cloud:      &PlanarGaussian3d,
+    padding:    f32,
+) -> Option<(Vec3, f32)> {
+
+    let splats: Vec<(Vec3, f32)> = cloud.position_visibility
+        .iter()
+        .zip(&cloud.scale_opacity)
+        .filter(|(pv, so)| pv.visibility > 0.0 && so.opacity > 0.0)
+        .map(|(pv, so)| (Vec3::from(pv.position), so.opacity))
+        .collect();
+
+    let weight: f32 = splats.iter().map(|(_, opacity)| opacity).sum();
+    if splats.is_empty() || weight <= 0.0 {
+        return None;
+    }
+
+    let center = splats.iter().map(|(p, opacity)| *p * *opacity).sum::<Vec3>() / weight;
+    let radius = splats.iter().map(|(p, _)| p.distance(center)).fold(0.0, f32::max);
+
+    // A single splat still needs some extent to frame
+    Some((center, radius.max(f32::EPSILON.sqrt()) * (1.0 + padding.max(0.0))))
+}
+
+
+
+fn look_at_from(
+    camera_transform:   &mut Transform,
+    target:             Vec3,
+    distance:           f32,
+) {
+
+    let forward = camera_transform.forward();
+
+    camera_transform.translation = target - *forward * distance;
+    camera_transform.look_to(forward, Vec3::Y);
+}
+
+
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use bevy_gaussian_splatting::Gaussian3d;
+
+    use super::*;
+    use crate::gaussian::cpu_transform::to_planar;
+
+    fn cloud(
+        points: &[Vec3]
+    ) -> PlanarGaussian3d {
+
+        let gaussians: Vec<Gaussian3d> = points
+            .iter()
+            .map(|p| {
+                let mut g = Gaussian3d::default();
+                g.position_visibility.position = p.to_array();
+                g
+            })
+            .collect();
+
+        to_planar(&gaussians)
+    }
+
+    #[test]
+    fn symmetric_eigen_diagonalizes() {
+        let rotation    = Mat3::from_quat(Quat::from_euler(EulerRot::XYZ, 0.4, -0.7, 1.1));
+        let matrix      = rotation * Mat3::from_diagonal(Vec3::new(16.0, 4.0, 0.25)) * rotation.transpose();
+
+        let (values, vectors) = symmetric_eigen(matrix);
+
+        for i in 0..3 {
+            let v = vectors.col(i);
+            assert!((matrix * v).abs_diff_eq(v * values[i], 1e-4), "{i}: {values}");
+        }
+    }
+
+    #[test]
+    fn obb_recovers_a_rotated_offset_box() {
+        let rotation    = Quat::from_euler(EulerRot::XYZ, 0.4, -0.7, 1.1);
+        let center      = Vec3::new(3.0, -2.0, 1.0);
+
+        let mut corners = Vec::new();
+        for x in [-4.0, 4.0] {
+            for y in [-2.0, 2.0] {
+                for z in [-0.5, 0.5] {
+                    corners.push(center + rotation * Vec3::new(x, y, z));
+                }
+            }
+        }
+
+        let (obb_center, obb_rotation, half_extents) = cloud_obb(&cloud(&corners));
+
+        assert!(obb_center.abs_diff_eq(center, 1e-3), "{obb_center}");
+        assert!(half_extents.abs_diff_eq(Vec3::new(4.0, 2.0, 0.5), 1e-3), "{half_extents}");
+        assert!(obb_rotation.is_normalized());
+
+        // Axes may come out flipped, but never swapped or tilted
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            assert!((obb_rotation * axis).dot(rotation * axis).abs() > 0.9999, "{axis}");
+        }
+    }
+
+    #[test]
+    fn obb_of_empty_and_single_point_clouds() {
+        assert_eq!(cloud_obb(&cloud(&[])), (Vec3::ZERO, Quat::IDENTITY, Vec3::ZERO));
+
+        let (center, rotation, half_extents) = cloud_obb(&cloud(&[Vec3::ONE]));
+        assert_eq!(center, Vec3::ONE);
+        assert_eq!(half_extents, Vec3::ZERO);
+        assert!(rotation.is_normalized());
+    }
+}
@@ -4,10 +4,34 @@ pub mod debug;
 
 pub mod beat_cauldron;
 
-use bevy::prelude::*;
-use bevy_gaussian_splatting::GaussianSplattingPlugin;
+use bevy::{prelude::*, render::RenderApp};
+use bevy_gaussian_splatting::{GaussianSplattingPlugin, PlanarGaussian3d};
 
-pub struct GenGaussianPlugin;
+/// Adds the gaussian splatting renderer and the mesh to gaussian conversion systems.
+///
+/// `GaussianSplattingPlugin` is only added if the app doesn't have it yet, so adding it
+/// yourself first is fine. Apps without a render app (e.g. `MinimalPlugins`) skip the renderer
+/// with a warning; use [`GenGaussianPlugin::without_renderer`] to do that deliberately.
+#[derive(Debug, Clone)]
+pub struct GenGaussianPlugin {
+    /// Add `GaussianSplattingPlugin`. When off, only the cloud and mesh asset types are
+    /// registered, so conversion and the CPU generation functions work headless. `AssetPlugin`
+    /// is added for them if the app doesn't have it yet (e.g. with `MinimalPlugins`).
+    pub renderer: bool,
+}
+
+impl Default for GenGaussianPlugin {
+    fn default() -> Self {
+        Self { renderer: true }
+    }
+}
+
+impl GenGaussianPlugin {
+    /// Plugin for headless, CPU-only use: no renderer and no render device needed.
+    pub fn without_renderer() -> Self {
+        Self { renderer: false }
+    }
+}
 
 fn log_gen_gaussian_startup() {
     info!(
@@ -20,7 +44,29 @@ impl Plugin for GenGaussianPlugin {
         // Do not add voxel plugin by default; it's orthogonal and can panic if not configured.
         // Ensure the gaussian splatting renderer and assets are registered globally.
         app.add_systems(Startup, log_gen_gaussian_startup);
-        app.add_plugins(GaussianSplattingPlugin);
+
+        let has_render_app = app.get_sub_app(RenderApp).is_some();
+        if self.renderer && !has_render_app {
+            warn!("GenGaussianPlugin: no render app (is RenderPlugin missing?); continuing without the renderer");
+        }
+
+        if self.renderer && has_render_app {
+            if !app.is_plugin_added::<GaussianSplattingPlugin>() {
+                app.add_plugins(GaussianSplattingPlugin);
+            }
+        } else {
+            // The conversion systems still need the asset types the renderer would register
+            if !app.is_plugin_added::<AssetPlugin>() {
+                app.add_plugins(AssetPlugin::default());
+            }
+            if !app.world().contains_resource::<Assets<PlanarGaussian3d>>() {
+                app.init_asset::<PlanarGaussian3d>();
+            }
+            if !app.world().contains_resource::<Assets<Mesh>>() {
+                app.init_asset::<Mesh>();
+            }
+        }
+
         // Our GPU mesh->gaussian conversion systems
         app.add_plugins(gaussian::GenGaussianGpuPlugin);
     }
@@ -31,11 +77,42 @@ pub use gaussian::*;
 
 /// Prelude module for convenient imports
 pub mod prelude {
+    pub use crate::GenGaussianPlugin;
     pub use crate::gaussian::{
+        CloudBaseSettings,
+        CloudOf,
+        CloudSortPeriod,
+        GenGaussianGpuPlugin,
+        MeshConverted,
+        MeshSelection,
+        MeshToGaussian,
+        MeshToGaussianBackend,
+        MeshToGaussianBuilder,
+        MeshToGaussianCloud,
+        MeshToGaussianGroup,
+        MeshToGaussianMode,
+        convert_loaded_mesh,
+        builder::*,
         cpu_mesh_to_gaussians::*,
+        cpu_transform::*,
+        io::*,
+        lod::*,
+        meshing::*,
         settings::*,
+        shapes::*,
+        spatial::*,
+        stats::*,
+        spawn::*,
         gpu_mesh_to_gaussians::*,
     };
+    pub use crate::sdf_module::{
+        SDFEdit,
+        SDFList,
+        primitives::*,
+        operations::*,
+        conversion::*,
+        terrain::*,
+    };
     pub use bevy_gaussian_splatting::{
         Gaussian3d, 
         PlanarGaussian3d, 
@@ -45,3 +122,17 @@ pub mod prelude {
     };
     pub use bevy::prelude::*;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headless_app_updates_without_the_renderer() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, GenGaussianPlugin::without_renderer()));
+
+        app.update();
+        app.update();
+    }
+}
@@ -8,10 +8,14 @@ use bevy::ecs::component::Component;
 
 pub mod primitives;
 pub mod operations;
+pub mod conversion;
+pub mod terrain;
 
 // Re-export main API
 pub use primitives::*;
 pub use operations::*; 
+pub use conversion::*;
+pub use terrain::*;
 
 
 
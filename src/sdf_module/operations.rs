@@ -48,6 +48,55 @@ impl SDF for TransformedSDF {
     }
 }
 
+/// Twisted SDF that rotates the XZ plane by `amount * y` radians before sampling.
+///
+/// Not an exact distance field; the gradient grows away from the Y axis, so convert it with a
+/// smaller `cell_size` and `thickness` in `SDFConversionSettings` than an undeformed shape needs.
+pub struct TwistSDF {
+    pub sdf: BoxedSDF,
+    pub amount: f32,
+}
+
+impl SDF for TwistSDF {
+    fn distance(&self, point: Vec3) -> f32 {
+        let (s, c) = (self.amount * point.y).sin_cos();
+        let local_point = Vec3::new(c * point.x - s * point.z, point.y, s * point.x + c * point.z);
+        self.sdf.distance(local_point)
+    }
+}
+
+/// Bent SDF that rotates the XY plane by `amount * x` radians before sampling, curving the
+/// X axis into an arc.
+///
+/// Like [`TwistSDF`] this is only an approximate distance field; see its notes on conversion.
+pub struct BendSDF {
+    pub sdf: BoxedSDF,
+    pub amount: f32,
+}
+
+impl SDF for BendSDF {
+    fn distance(&self, point: Vec3) -> f32 {
+        let (s, c) = (self.amount * point.x).sin_cos();
+        let local_point = Vec3::new(c * point.x - s * point.y, s * point.x + c * point.y, point.z);
+        self.sdf.distance(local_point)
+    }
+}
+
+/// Mirrored SDF that reflects the negative side of the plane through the origin with normal
+/// `axis` onto the positive side, so the shape appears on both sides of it.
+pub struct MirrorSDF {
+    pub sdf: BoxedSDF,
+    pub axis: Vec3,
+}
+
+impl SDF for MirrorSDF {
+    fn distance(&self, point: Vec3) -> f32 {
+        let axis = self.axis.normalize_or_zero();
+        let local_point = point - 2.0 * point.dot(axis).min(0.0) * axis;
+        self.sdf.distance(local_point)
+    }
+}
+
 /// Combine two SDFs with an operation
 pub fn combine_sdfs(left: BoxedSDF, right: BoxedSDF, operation: SDFOperation) -> BoxedSDF {
     Box::new(CombinedSDF {
@@ -66,6 +115,21 @@ pub fn transform_sdf(sdf: BoxedSDF, transform: Transform) -> BoxedSDF {
     })
 }
 
+/// Twist an SDF around the Y axis
+pub fn twist_sdf(sdf: BoxedSDF, amount: f32) -> BoxedSDF {
+    Box::new(TwistSDF { sdf, amount })
+}
+
+/// Bend an SDF along the X axis
+pub fn bend_sdf(sdf: BoxedSDF, amount: f32) -> BoxedSDF {
+    Box::new(BendSDF { sdf, amount })
+}
+
+/// Mirror an SDF across the plane through the origin with normal `axis`
+pub fn mirror_sdf(sdf: BoxedSDF, axis: Vec3) -> BoxedSDF {
+    Box::new(MirrorSDF { sdf, axis })
+}
+
 /// Smooth minimum function for smooth unions
 fn smooth_min(a: f32, b: f32, k: f32) -> f32 {
     let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
@@ -83,7 +147,12 @@ pub trait SDFExt {
     fn intersection(self, other: BoxedSDF) -> BoxedSDF;
     fn subtraction(self, other: BoxedSDF) -> BoxedSDF;
     fn smooth_union(self, other: BoxedSDF, smoothness: f32) -> BoxedSDF;
+    fn smooth_intersection(self, other: BoxedSDF, smoothness: f32) -> BoxedSDF;
+    fn smooth_subtraction(self, other: BoxedSDF, smoothness: f32) -> BoxedSDF;
     fn transform(self, transform: Transform) -> BoxedSDF;
+    fn twist(self, amount: f32) -> BoxedSDF;
+    fn bend(self, amount: f32) -> BoxedSDF;
+    fn mirror(self, axis: Vec3) -> BoxedSDF;
 }
 
 impl SDFExt for BoxedSDF {
@@ -103,7 +172,27 @@ impl SDFExt for BoxedSDF {
         combine_sdfs(self, other, SDFOperation::SmoothUnion(smoothness))
     }
     
+    fn smooth_intersection(self, other: BoxedSDF, smoothness: f32) -> BoxedSDF {
+        combine_sdfs(self, other, SDFOperation::SmoothIntersection(smoothness))
+    }
+    
+    fn smooth_subtraction(self, other: BoxedSDF, smoothness: f32) -> BoxedSDF {
+        combine_sdfs(self, other, SDFOperation::SmoothSubtraction(smoothness))
+    }
+    
     fn transform(self, transform: Transform) -> BoxedSDF {
         transform_sdf(self, transform)
     }
+    
+    fn twist(self, amount: f32) -> BoxedSDF {
+        twist_sdf(self, amount)
+    }
+    
+    fn bend(self, amount: f32) -> BoxedSDF {
+        bend_sdf(self, amount)
+    }
+    
+    fn mirror(self, axis: Vec3) -> BoxedSDF {
+        mirror_sdf(self, axis)
+    }
 }
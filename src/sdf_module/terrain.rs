@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use noise::{Fbm, MultiFractal, NoiseFn, Perlin};
+
+use super::primitives::{SDF, BoxedSDF};
+
+
+
+
+
+/// Fractal noise parameters for procedural terrain
+#[derive(Debug, Clone, Copy)]
+pub struct TerrainParams {
+    pub octaves: usize,
+    pub frequency: f64,
+    pub lacunarity: f64,
+    pub persistence: f64,
+    /// World-space height the ground surface oscillates around
+    pub base_height: f32,
+    /// Vertical extent of the noise displacement, in world units
+    pub amplitude: f32,
+}
+
+impl Default for TerrainParams {
+    fn default() -> Self {
+        Self {
+            octaves: 5,
+            frequency: 0.05,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            base_height: 0.0,
+            amplitude: 8.0,
+        }
+    }
+}
+
+
+
+
+
+/// Terrain SDF built from 3D fractal Perlin density.
+///
+/// The noise is sampled in full 3D rather than as a heightfield, so the surface can fold back
+/// on itself into overhangs and caves. Distances are only approximate near steep features;
+/// treat the result as a density field rather than an exact SDF.
+pub struct TerrainSDF {
+    params: TerrainParams,
+    noise: Fbm<Perlin>,
+}
+
+impl TerrainSDF {
+    pub fn new(seed: u32, params: TerrainParams) -> Self {
+        let noise = Fbm::<Perlin>::new(seed)
+            .set_octaves(params.octaves)
+            .set_frequency(params.frequency)
+            .set_lacunarity(params.lacunarity)
+            .set_persistence(params.persistence);
+
+        Self { params, noise }
+    }
+
+    /// The parameters the noise was built with; build a new terrain to change them.
+    pub fn params(&self) -> &TerrainParams {
+        &self.params
+    }
+}
+
+impl SDF for TerrainSDF {
+    fn distance(&self, point: Vec3) -> f32 {
+        let density = self.noise.get([point.x as f64, point.y as f64, point.z as f64]) as f32;
+        (point.y - self.params.base_height) - density * self.params.amplitude
+    }
+}
+
+
+
+
+
+pub fn sdf_terrain(seed: u32, params: TerrainParams) -> BoxedSDF {
+    Box::new(TerrainSDF::new(seed, params))
+}